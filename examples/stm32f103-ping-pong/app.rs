@@ -1,7 +1,5 @@
-#![no_std]
-#![no_main]
-
 use sx126x::conf::Config as LoRaConfig;
+use sx126x::hal02;
 use sx126x::op::status::CommandStatus::{CommandTimeout, CommandTxDone, DataAvailable};
 use sx126x::op::*;
 use sx126x::SX126x;
@@ -11,7 +9,7 @@ use core::sync::atomic::{AtomicBool, Ordering};
 
 use cortex_m_rt::entry;
 use cortex_m_semihosting::{hprint, hprintln};
-use embedded_hal::digital::v2::OutputPin;
+use embedded_hal_02::digital::v2::OutputPin;
 use panic_semihosting as _;
 use stm32f1xx_hal::delay::Delay;
 use stm32f1xx_hal::gpio::*;
@@ -23,8 +21,7 @@ use stm32::interrupt;
 
 type Dio1Pin = gpiob::PB10<Input<Floating>>;
 
-const RF_FREQUENCY: u32 = 868_000_000; // 868MHz (EU)
-const F_XTAL: u32 = 32_000_000; // 32MHz
+const RF_FREQUENCY: Frequency = Frequency::from_hz(868_000_000); // 868MHz (EU)
 
 /// Static reference to the DIO1 pin, for use in the ISR EXTi15_10
 static mut DIO1_PIN: MaybeUninit<Dio1Pin> = MaybeUninit::uninit();
@@ -74,7 +71,7 @@ fn main() -> ! {
         spi1_mosi, // D11
     );
 
-    let spi1 = &mut Spi::spi1(
+    let spi1 = Spi::spi1(
         peripherals.SPI1,
         spi1_pins,
         &mut afio.mapr,
@@ -107,17 +104,19 @@ fn main() -> ! {
     lora_dio1.enable_interrupt(&exti);
     // Wrap DIO1 pin in Dio1PinRefMut newtype, as mutable refences to
     // pins do not implement the `embedded_hal::digital::v2::InputPin` trait.
-    let mut lora_dio1 = Dio1PinRefMut(lora_dio1);
+    let mut lora_dio1 = hal02::Input(Dio1PinRefMut(lora_dio1));
+
+    // Combine SPI1 and the nss pin into an SPI device
+    let spi1 = &mut hal02::SlaveSelect::new(spi1, lora_nss); // nss: D7
 
     let lora_pins = (
-        lora_nss,    // D7
-        lora_nreset, // A0
-        lora_busy,   // D4
-        lora_ant,    // D8
+        hal02::Output(lora_nreset), // A0
+        hal02::Input(lora_busy),    // D4
+        hal02::Output(lora_ant),    // D8
     );
 
     // Initialize a busy-waiting delay based on the system clock
-    let delay = &mut Delay::new(core_peripherals.SYST, clocks);
+    let delay = &mut hal02::Delay(Delay::new(core_peripherals.SYST, clocks));
 
     // ===== Init LoRa modem =====
     let conf = build_config();
@@ -192,8 +191,11 @@ fn main() -> ! {
 
 fn build_config() -> LoRaConfig {
     use sx126x::op::{
-        irq::IrqMaskBit::*, modulation::lora::*, packet::lora::LoRaPacketParams,
-        rxtx::DeviceSel::SX1261, PacketType::LoRa,
+        irq::IrqMaskBit::{RxDone, Timeout, TxDone},
+        modulation::lora::*,
+        packet::lora::LoRaPacketParams,
+        rxtx::DeviceSel::SX1261,
+        PacketType::LoRa,
     };
 
     let mod_params = LoraModParams::default().into();
@@ -211,9 +213,10 @@ fn build_config() -> LoRaConfig {
 
     let packet_params = LoRaPacketParams::default().into();
 
-    let rf_freq = sx126x::calc_rf_freq(RF_FREQUENCY as f32, F_XTAL as f32);
-
     LoRaConfig {
+        variant: Variant::SX1261,
+        regulator_mode: RegulatorMode::Ldo,
+        tcxo: None,
         packet_type: LoRa,
         sync_word: 0x1424, // Private networks
         calib_param: CalibParam::from(0x7F),
@@ -225,7 +228,6 @@ fn build_config() -> LoRaConfig {
         dio2_irq_mask: IrqMask::none(),
         dio3_irq_mask: IrqMask::none(),
         rf_frequency: RF_FREQUENCY,
-        rf_freq,
     }
 }
 
@@ -234,7 +236,7 @@ fn build_config() -> LoRaConfig {
 /// which is necessary in order to pass it to the SX126x driver.
 struct Dio1PinRefMut<'dio1>(&'dio1 mut Dio1Pin);
 
-impl<'dio1> embedded_hal::digital::v2::InputPin for Dio1PinRefMut<'dio1> {
+impl<'dio1> embedded_hal_02::digital::v2::InputPin for Dio1PinRefMut<'dio1> {
     type Error = core::convert::Infallible;
 
     fn is_high(&self) -> Result<bool, Self::Error> {
@@ -0,0 +1,9 @@
+#![cfg_attr(target_os = "none", no_std, no_main)]
+
+// The example runs on a STM32F103. On other targets it is an empty program,
+// so that `cargo test` on the host can still build all examples
+#[cfg(target_os = "none")]
+mod app;
+
+#[cfg(not(target_os = "none"))]
+fn main() {}
@@ -1,9 +1,19 @@
 //! Wrapper for modem configuration parameters
+use core::convert::TryFrom;
+
+use super::op::modulation::lora::{LoRaBandWidth, LoRaSpreadFactor, LoraModParams};
 use super::op::*;
 
 /// Configuration parameters.
 /// Used to initialize the SX126x modem
+#[derive(Copy, Clone)]
 pub struct Config {
+    /// Chip variant the configuration is validated against
+    pub variant: Variant,
+    /// Regulator mode
+    pub regulator_mode: RegulatorMode,
+    /// TCXO powered through the dio3 pin. Set to None if the modem uses a crystal
+    pub tcxo: Option<TcxoConfig>,
     /// Packet type
     pub packet_type: PacketType,
     /// LoRa sync word
@@ -25,8 +35,166 @@ pub struct Config {
     pub dio2_irq_mask: IrqMask,
     /// DIO3 IRW mask
     pub dio3_irq_mask: IrqMask,
-    /// RF freq, calculated using crate::calc_rf_freq
-    pub rf_freq: u32,
-    /// RF frequency in MHz
-    pub rf_frequency: u32,
+    /// RF frequency
+    pub rf_frequency: Frequency,
+}
+
+/// Reasons a configuration is not supported by its chip variant
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The RF frequency in Hz is out of the range of the variant
+    RfFrequency(u32),
+    /// The PA selected in the PA configuration is not available on the variant
+    DeviceSel(DeviceSel),
+    /// The TX power in dBm is out of the range of the PA of the variant
+    TxPower(i8),
+    /// The variant does not support this LoRa spreading factor and bandwidth combination
+    LoRaModulation {
+        spread_factor: LoRaSpreadFactor,
+        bandwidth: LoRaBandWidth,
+    },
+    /// The LoRa modulation parameters could not be decoded
+    ModParams,
+}
+
+impl Config {
+    /// Check whether the configuration is supported by its chip variant.
+    /// Called by SX126x::init
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let variant = self.variant;
+        variant.check_rf_frequency(self.rf_frequency)?;
+        variant.check_pa_config(self.pa_config)?;
+        variant.check_tx_params(self.tx_params)?;
+        variant.check_mod_params(self.packet_type, self.mod_params)
+    }
+}
+
+/// Checks of single parameters, used by Config::validate and by the
+/// SX126x setters once the modem is initialized
+impl Variant {
+    /// Check whether the RF frequency is in the range of the variant
+    pub fn check_rf_frequency(self, rf_frequency: Frequency) -> Result<(), ConfigError> {
+        let rf_frequency = rf_frequency.hz();
+        if !self.frequency_range().contains(&rf_frequency) {
+            return Err(ConfigError::RfFrequency(rf_frequency));
+        }
+        Ok(())
+    }
+
+    /// Check whether the PA selected in the PA configuration is available on the variant
+    pub fn check_pa_config(self, pa_config: PaConfig) -> Result<(), ConfigError> {
+        let device_sel = pa_config.device_sel();
+        if device_sel != self.device_sel() {
+            return Err(ConfigError::DeviceSel(device_sel));
+        }
+        Ok(())
+    }
+
+    /// Check whether the TX power is in the range of the PA of the variant
+    pub fn check_tx_params(self, tx_params: TxParams) -> Result<(), ConfigError> {
+        let power_dbm = tx_params.power_dbm();
+        if !self.tx_power_range().contains(&power_dbm) {
+            return Err(ConfigError::TxPower(power_dbm));
+        }
+        Ok(())
+    }
+
+    /// Check whether the variant supports the modulation. Only LoRa modulations
+    /// are restricted, GFSK parameters are not checked
+    pub fn check_mod_params(
+        self,
+        packet_type: PacketType,
+        mod_params: ModParams,
+    ) -> Result<(), ConfigError> {
+        if packet_type != PacketType::LoRa {
+            return Ok(());
+        }
+        let params = LoraModParams::try_from(mod_params).map_err(|_| ConfigError::ModParams)?;
+        let spread_factor = params.spread_factor();
+        let bandwidth = params.bandwidth();
+        if !self.supports_lora(spread_factor, bandwidth) {
+            return Err(ConfigError::LoRaModulation {
+                spread_factor,
+                bandwidth,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lora(spread_factor: LoRaSpreadFactor, bandwidth: LoRaBandWidth) -> ModParams {
+        LoraModParams::default()
+            .set_spread_factor(spread_factor)
+            .set_bandwidth(bandwidth)
+            .into()
+    }
+
+    #[test]
+    fn llcc68_lora_limits() {
+        use LoRaBandWidth::*;
+        use LoRaSpreadFactor::*;
+
+        let check = |spread_factor, bandwidth| {
+            Variant::LLCC68.check_mod_params(PacketType::LoRa, lora(spread_factor, bandwidth))
+        };
+        for &(bandwidth, max_spread_factor, too_high) in
+            &[(BW125, SF9, SF10), (BW250, SF10, SF11), (BW500, SF11, SF12)]
+        {
+            assert_eq!(check(SF5, bandwidth), Ok(()));
+            assert_eq!(check(max_spread_factor, bandwidth), Ok(()));
+            assert_eq!(
+                check(too_high, bandwidth),
+                Err(ConfigError::LoRaModulation {
+                    spread_factor: too_high,
+                    bandwidth,
+                })
+            );
+        }
+        assert_eq!(
+            check(SF7, BW62),
+            Err(ConfigError::LoRaModulation {
+                spread_factor: SF7,
+                bandwidth: BW62,
+            })
+        );
+
+        // The other variants support every combination
+        let params = lora(SF12, BW7);
+        assert_eq!(
+            Variant::SX1262.check_mod_params(PacketType::LoRa, params),
+            Ok(())
+        );
+        // GFSK parameters are not checked
+        assert_eq!(
+            Variant::LLCC68.check_mod_params(PacketType::GFSK, params),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn sx1268_frequency_range() {
+        let check = |hz| Variant::SX1268.check_rf_frequency(Frequency::from_hz(hz));
+        assert_eq!(check(410_000_000), Ok(()));
+        assert_eq!(check(470_000_000), Ok(()));
+        assert_eq!(check(810_000_000), Ok(()));
+        assert_eq!(
+            check(409_999_999),
+            Err(ConfigError::RfFrequency(409_999_999))
+        );
+        assert_eq!(
+            check(868_000_000),
+            Err(ConfigError::RfFrequency(868_000_000))
+        );
+
+        let check = |hz| Variant::SX1262.check_rf_frequency(Frequency::from_hz(hz));
+        assert_eq!(check(868_000_000), Ok(()));
+        assert_eq!(
+            check(960_000_001),
+            Err(ConfigError::RfFrequency(960_000_001))
+        );
+    }
 }
@@ -0,0 +1,178 @@
+//! Adapters to use the driver with peripherals implementing the embedded-hal 0.2 traits.
+//! Enable the `embedded-hal-02` feature to use them.
+use core::fmt::Debug;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{self, InputPin, OutputPin};
+use embedded_hal::spi::{self, Operation, SpiDevice};
+use embedded_hal_02::blocking::delay::DelayUs;
+use embedded_hal_02::blocking::spi::{Transfer, Write};
+use embedded_hal_02::digital::v2 as digital02;
+
+const NOP: u8 = 0x00;
+
+/// Error of an embedded-hal 0.2 pin
+#[derive(Debug)]
+pub struct PinError<TPINERR>(pub TPINERR);
+
+impl<TPINERR: Debug> digital::Error for PinError<TPINERR> {
+    fn kind(&self) -> digital::ErrorKind {
+        digital::ErrorKind::Other
+    }
+}
+
+/// Wraps an embedded-hal 0.2 input pin, like the busy and dio1 pins
+pub struct Input<TPIN>(pub TPIN);
+
+impl<TPIN> digital::ErrorType for Input<TPIN>
+where
+    TPIN: digital02::InputPin,
+    TPIN::Error: Debug,
+{
+    type Error = PinError<TPIN::Error>;
+}
+
+impl<TPIN> InputPin for Input<TPIN>
+where
+    TPIN: digital02::InputPin,
+    TPIN::Error: Debug,
+{
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.0.is_high().map_err(PinError)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.0.is_low().map_err(PinError)
+    }
+}
+
+/// Wraps an embedded-hal 0.2 output pin, like the nrst and ant pins
+pub struct Output<TPIN>(pub TPIN);
+
+impl<TPIN> digital::ErrorType for Output<TPIN>
+where
+    TPIN: digital02::OutputPin,
+    TPIN::Error: Debug,
+{
+    type Error = PinError<TPIN::Error>;
+}
+
+impl<TPIN> OutputPin for Output<TPIN>
+where
+    TPIN: digital02::OutputPin,
+    TPIN::Error: Debug,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.set_low().map_err(PinError)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.set_high().map_err(PinError)
+    }
+}
+
+/// Wraps an embedded-hal 0.2 delay with microsecond resolution
+pub struct Delay<TDELAY>(pub TDELAY);
+
+impl<TDELAY: DelayUs<u32>> DelayNs for Delay<TDELAY> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_us(ns.div_ceil(1000));
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us);
+    }
+}
+
+/// Error of a SlaveSelect transaction
+#[derive(Debug)]
+pub enum SlaveSelectError<TSPIERR, TPINERR> {
+    /// Error of the underlying SPI bus
+    Spi(TSPIERR),
+    /// Error of the nss pin
+    Nss(TPINERR),
+}
+
+impl<TSPIERR: Debug, TPINERR: Debug> spi::Error for SlaveSelectError<TSPIERR, TPINERR> {
+    fn kind(&self) -> spi::ErrorKind {
+        match self {
+            Self::Spi(_) => spi::ErrorKind::Other,
+            Self::Nss(_) => spi::ErrorKind::ChipSelectFault,
+        }
+    }
+}
+
+/// SPI device built from an embedded-hal 0.2 SPI bus and nss pin.
+/// The nss pin is pulled low for the duration of each transaction.
+/// Note that delay operations are not supported and are skipped.
+pub struct SlaveSelect<TSPI, TNSS> {
+    spi: TSPI,
+    nss: TNSS,
+}
+
+impl<TSPI, TNSS> SlaveSelect<TSPI, TNSS> {
+    pub fn new(spi: TSPI, nss: TNSS) -> Self {
+        Self { spi, nss }
+    }
+
+    /// Release the SPI bus and nss pin
+    pub fn free(self) -> (TSPI, TNSS) {
+        (self.spi, self.nss)
+    }
+}
+
+impl<TSPI, TNSS, TSPIERR> SlaveSelect<TSPI, TNSS>
+where
+    TSPI: Write<u8, Error = TSPIERR> + Transfer<u8, Error = TSPIERR>,
+{
+    fn run(&mut self, operation: &mut Operation<'_, u8>) -> Result<(), TSPIERR> {
+        match operation {
+            Operation::Read(words) => {
+                words.fill(NOP);
+                self.spi.transfer(words).map(|_| {})
+            }
+            Operation::Write(words) => self.spi.write(words),
+            Operation::Transfer(read, write) => {
+                for i in 0..read.len().max(write.len()) {
+                    let mut word = [write.get(i).copied().unwrap_or(NOP)];
+                    self.spi.transfer(&mut word)?;
+                    if let Some(read) = read.get_mut(i) {
+                        *read = word[0];
+                    }
+                }
+                Ok(())
+            }
+            Operation::TransferInPlace(words) => self.spi.transfer(words).map(|_| {}),
+            Operation::DelayNs(_) => Ok(()),
+        }
+    }
+}
+
+impl<TSPI, TNSS, TSPIERR> spi::ErrorType for SlaveSelect<TSPI, TNSS>
+where
+    TSPI: Write<u8, Error = TSPIERR> + Transfer<u8, Error = TSPIERR>,
+    TNSS: digital02::OutputPin,
+    TSPIERR: Debug,
+    TNSS::Error: Debug,
+{
+    type Error = SlaveSelectError<TSPIERR, TNSS::Error>;
+}
+
+impl<TSPI, TNSS, TSPIERR> SpiDevice for SlaveSelect<TSPI, TNSS>
+where
+    TSPI: Write<u8, Error = TSPIERR> + Transfer<u8, Error = TSPIERR>,
+    TNSS: digital02::OutputPin,
+    TSPIERR: Debug,
+    TNSS::Error: Debug,
+{
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        self.nss.set_low().map_err(SlaveSelectError::Nss)?;
+        let result = operations
+            .iter_mut()
+            .try_for_each(|operation| self.run(operation))
+            .map_err(SlaveSelectError::Spi);
+        // Release nss, even if the transaction failed
+        let nss = self.nss.set_high().map_err(SlaveSelectError::Nss);
+        result.and(nss)
+    }
+}
@@ -1,8 +1,16 @@
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
 pub mod conf;
+#[cfg(feature = "embedded-hal-02")]
+pub mod hal02;
 pub mod op;
 pub mod reg;
+#[cfg(feature = "sim")]
+pub mod sim;
+pub mod trace;
 
 mod sx;
 pub use sx::*;
@@ -0,0 +1,144 @@
+use core::convert::{TryFrom, TryInto};
+
+use super::err::InvalidValue;
+use super::RxTxTimeout;
+
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CadSymbolNum {
+    Symbols1 = 0x00,
+    Symbols2 = 0x01,
+    Symbols4 = 0x02,
+    Symbols8 = 0x03,
+    Symbols16 = 0x04,
+}
+
+impl_try_from_u8!(
+    CadSymbolNum,
+    [Symbols1, Symbols2, Symbols4, Symbols8, Symbols16]
+);
+
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CadExitMode {
+    /// Perform the CAD and return to STDBY_RC mode
+    CadOnly = 0x00,
+    /// Perform the CAD and stay in RX mode if activity was detected,
+    /// until a packet is received or the CAD timeout elapses
+    CadRx = 0x01,
+}
+
+impl_try_from_u8!(CadExitMode, [CadOnly, CadRx]);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CadParams {
+    symbol_num: CadSymbolNum,
+    det_peak: u8,
+    det_min: u8,
+    exit_mode: CadExitMode,
+    timeout: RxTxTimeout,
+}
+
+impl Default for CadParams {
+    fn default() -> Self {
+        Self {
+            symbol_num: CadSymbolNum::Symbols2,
+            det_peak: 22,
+            det_min: 10,
+            exit_mode: CadExitMode::CadOnly,
+            timeout: 0.into(),
+        }
+    }
+}
+
+impl From<CadParams> for [u8; 7] {
+    fn from(params: CadParams) -> Self {
+        let timeout: [u8; 3] = params.timeout.into();
+        [
+            params.symbol_num as u8,
+            params.det_peak,
+            params.det_min,
+            params.exit_mode as u8,
+            timeout[0],
+            timeout[1],
+            timeout[2],
+        ]
+    }
+}
+
+impl TryFrom<[u8; 7]> for CadParams {
+    type Error = InvalidValue;
+
+    fn try_from(raw: [u8; 7]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            symbol_num: raw[0].try_into()?,
+            det_peak: raw[1],
+            det_min: raw[2],
+            exit_mode: raw[3].try_into()?,
+            timeout: [raw[4], raw[5], raw[6]].into(),
+        })
+    }
+}
+
+impl CadParams {
+    /// Set the number of symbols used for the CAD
+    pub fn set_symbol_num(mut self, symbol_num: CadSymbolNum) -> Self {
+        self.symbol_num = symbol_num;
+        self
+    }
+
+    /// Set the detection peak threshold. The optimal value depends on
+    /// the spreading factor and bandwidth, see application note AN1200.48
+    pub fn set_det_peak(mut self, det_peak: u8) -> Self {
+        self.det_peak = det_peak;
+        self
+    }
+
+    /// Set the minimum peak value, used to reject false detections
+    pub fn set_det_min(mut self, det_min: u8) -> Self {
+        self.det_min = det_min;
+        self
+    }
+
+    pub fn set_exit_mode(mut self, exit_mode: CadExitMode) -> Self {
+        self.exit_mode = exit_mode;
+        self
+    }
+
+    /// Set the time the modem stays in RX after activity was detected.
+    /// Only used if the exit mode is CadExitMode::CadRx
+    pub fn set_timeout(mut self, timeout: RxTxTimeout) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+
+    use super::{CadExitMode, CadParams, CadSymbolNum};
+    use crate::op::RxTxTimeout;
+
+    #[test]
+    fn cad_params_round_trip() {
+        let params = CadParams::default()
+            .set_symbol_num(CadSymbolNum::Symbols8)
+            .set_det_peak(24)
+            .set_det_min(10)
+            .set_exit_mode(CadExitMode::CadRx)
+            .set_timeout(RxTxTimeout::from_ms(100));
+        let raw = <[u8; 7]>::from(params);
+        assert_eq!(CadParams::try_from(raw), Ok(params));
+    }
+
+    #[test]
+    fn invalid_cad_params() {
+        let mut raw = <[u8; 7]>::from(CadParams::default());
+        raw[0] = 0x05;
+        assert!(CadParams::try_from(raw).is_err());
+        let mut raw = <[u8; 7]>::from(CadParams::default());
+        raw[3] = 0x02;
+        assert!(CadParams::try_from(raw).is_err());
+    }
+}
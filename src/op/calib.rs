@@ -1,4 +1,4 @@
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub struct CalibParam {
     inner: u8,
 }
@@ -40,23 +40,56 @@ impl CalibParam {
     }
 }
 
-#[derive(Copy, Clone)]
-#[repr(u16)]
-pub enum CalibImageFreq {
-    MHz430_440 = 0x6B_6F,
-    MHz470_510 = 0x75_81,
-    MHz779_787 = 0xC1_C5,
-    MHz863_870 = 0xD7_DB,
-    MHz902_928 = 0xE1_E9,
+/// Frequency band of the image calibration, in steps of 4 MHz, see 9.2.1
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CalibImageFreq {
+    freq1: u8,
+    freq2: u8,
 }
 
-impl Into<[u8; 2]> for CalibImageFreq {
-    fn into(self) -> [u8; 2] {
-        (self as u16).to_be_bytes()
+impl From<CalibImageFreq> for [u8; 2] {
+    fn from(freq: CalibImageFreq) -> Self {
+        [freq.freq1, freq.freq2]
     }
 }
 
+impl From<[u8; 2]> for CalibImageFreq {
+    fn from(raw: [u8; 2]) -> Self {
+        Self::new(raw[0], raw[1])
+    }
+}
+
+#[allow(non_upper_case_globals)]
 impl CalibImageFreq {
+    /// Step size of the band limits in Hz
+    const STEP: u32 = 4_000_000;
+
+    // Table 9-2: Image calibration for the usual ISM bands
+    pub const MHz430_440: Self = Self::new(0x6B, 0x6F);
+    pub const MHz470_510: Self = Self::new(0x75, 0x81);
+    pub const MHz779_787: Self = Self::new(0xC1, 0xC5);
+    pub const MHz863_870: Self = Self::new(0xD7, 0xDB);
+    pub const MHz902_928: Self = Self::new(0xE1, 0xE9);
+
+    /// Calibrate the band from freq1 * 4 MHz to freq2 * 4 MHz
+    pub const fn new(freq1: u8, freq2: u8) -> Self {
+        Self { freq1, freq2 }
+    }
+
+    /// Calibrate the band from `low` to `high` Hz. The lower limit is rounded down
+    /// and the upper limit is rounded up to a multiple of 4 MHz
+    pub const fn from_range(low: u32, high: u32) -> Self {
+        let step = Self::STEP as u64;
+        let freq1 = low as u64 / step;
+        let freq2 = (high as u64).div_ceil(step);
+        Self {
+            freq1: if freq1 > 0xFF { 0xFF } else { freq1 as u8 },
+            freq2: if freq2 > 0xFF { 0xFF } else { freq2 as u8 },
+        }
+    }
+
+    /// Band to calibrate for an RF frequency in Hz. This is the matching ISM band
+    /// from table 9-2 if there is one, or the 4 MHz step containing the frequency otherwise
     pub const fn from_rf_frequency(rf_frequency: u32) -> Self {
         match rf_frequency / 1000000 {
             902..=928 => Self::MHz902_928,
@@ -64,7 +97,83 @@ impl CalibImageFreq {
             779..=787 => Self::MHz779_787,
             470..=510 => Self::MHz470_510,
             430..=440 => Self::MHz430_440,
-            _ => Self::MHz902_928, // Default
+            _ => {
+                let freq1 = Self::from_range(rf_frequency, rf_frequency).freq1;
+                Self::new(freq1, freq1.saturating_add(1))
+            }
+        }
+    }
+
+    /// Lower limit of the band in steps of 4 MHz
+    pub fn freq1(&self) -> u8 {
+        self.freq1
+    }
+
+    /// Upper limit of the band in steps of 4 MHz
+    pub fn freq2(&self) -> u8 {
+        self.freq2
+    }
+
+    /// Whether the band contains the RF frequency in Hz
+    pub fn contains(&self, rf_frequency: u32) -> bool {
+        let low = self.freq1 as u32 * Self::STEP;
+        let high = self.freq2 as u32 * Self::STEP;
+        (low..=high).contains(&rf_frequency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CalibImageFreq;
+
+    #[test]
+    fn calib_image_freq_from_range() {
+        // 915 MHz is rounded down to 912 MHz, 928.5 MHz up to 932 MHz
+        let freq = CalibImageFreq::from_range(915_000_000, 928_500_000);
+        assert_eq!(<[u8; 2]>::from(freq), [0xE4, 0xE9]);
+        // Limits on a step are kept
+        let freq = CalibImageFreq::from_range(432_000_000, 436_000_000);
+        assert_eq!(<[u8; 2]>::from(freq), [0x6C, 0x6D]);
+        // Limits above 1020 MHz saturate
+        let freq = CalibImageFreq::from_range(u32::MAX, u32::MAX);
+        assert_eq!(<[u8; 2]>::from(freq), [0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn calib_image_freq_contains() {
+        let band = CalibImageFreq::MHz902_928;
+        assert!(band.contains(900_000_000));
+        assert!(band.contains(932_000_000));
+        assert!(!band.contains(899_999_999));
+        assert!(!band.contains(932_000_001));
+
+        let band = CalibImageFreq::from_range(915_000_000, 928_500_000);
+        assert!(band.contains(915_000_000));
+        assert!(band.contains(928_500_000));
+        assert!(!band.contains(911_999_999));
+    }
+
+    #[test]
+    fn calib_image_freq_from_rf_frequency() {
+        use CalibImageFreq as F;
+
+        for &(rf_frequency, band) in &[
+            // Edges of the 902-928 MHz band
+            (902_000_000, F::MHz902_928),
+            (915_000_000, F::MHz902_928),
+            (928_000_000, F::MHz902_928),
+            (928_999_999, F::MHz902_928),
+            (433_175_000, F::MHz430_440),
+            (434_665_000, F::MHz430_440),
+            (868_000_000, F::MHz863_870),
+            // Out of the ISM bands, the 4 MHz step containing the frequency
+            (929_000_000, F::new(0xE8, 0xE9)),
+            (901_999_999, F::new(0xE1, 0xE2)),
+            (950_000_000, F::new(0xED, 0xEE)),
+        ] {
+            let freq = F::from_rf_frequency(rf_frequency);
+            assert_eq!(freq, band, "{} Hz", rf_frequency);
+            assert!(freq.contains(rf_frequency), "{} Hz", rf_frequency);
         }
     }
 }
@@ -0,0 +1,384 @@
+/// Opcodes of the commands, see tables 11-1 to 11-5
+pub mod opcode {
+    pub const SET_SLEEP: u8 = 0x84;
+    pub const SET_STANDBY: u8 = 0x80;
+    pub const SET_FS: u8 = 0xC1;
+    pub const SET_TX: u8 = 0x83;
+    pub const SET_RX: u8 = 0x82;
+    pub const STOP_TIMER_ON_PREAMBLE: u8 = 0x9F;
+    pub const SET_RX_DUTY_CYCLE: u8 = 0x94;
+    pub const SET_CAD: u8 = 0xC5;
+    pub const SET_TX_CONTINUOUS_WAVE: u8 = 0xD1;
+    pub const SET_TX_INFINITE_PREAMBLE: u8 = 0xD2;
+    pub const SET_REGULATOR_MODE: u8 = 0x96;
+    pub const CALIBRATE: u8 = 0x89;
+    pub const CALIBRATE_IMAGE: u8 = 0x98;
+    pub const SET_PA_CONFIG: u8 = 0x95;
+    pub const SET_RX_TX_FALLBACK_MODE: u8 = 0x93;
+    pub const WRITE_REGISTER: u8 = 0x0D;
+    pub const READ_REGISTER: u8 = 0x1D;
+    pub const WRITE_BUFFER: u8 = 0x0E;
+    pub const READ_BUFFER: u8 = 0x1E;
+    pub const SET_DIO_IRQ_PARAMS: u8 = 0x08;
+    pub const GET_IRQ_STATUS: u8 = 0x12;
+    pub const CLEAR_IRQ_STATUS: u8 = 0x02;
+    pub const SET_DIO2_AS_RF_SWITCH_CTRL: u8 = 0x9D;
+    pub const SET_DIO3_AS_TCXO_CTRL: u8 = 0x97;
+    pub const SET_RF_FREQUENCY: u8 = 0x86;
+    pub const SET_PACKET_TYPE: u8 = 0x8A;
+    pub const GET_PACKET_TYPE: u8 = 0x11;
+    pub const SET_TX_PARAMS: u8 = 0x8E;
+    pub const SET_MODULATION_PARAMS: u8 = 0x8B;
+    pub const SET_PACKET_PARAMS: u8 = 0x8C;
+    pub const SET_CAD_PARAMS: u8 = 0x88;
+    pub const SET_BUFFER_BASE_ADDRESS: u8 = 0x8F;
+    pub const SET_LORA_SYMB_NUM_TIMEOUT: u8 = 0xA0;
+    pub const GET_STATUS: u8 = 0xC0;
+    pub const GET_RSSI_INST: u8 = 0x15;
+    pub const GET_RX_BUFFER_STATUS: u8 = 0x13;
+    pub const GET_PACKET_STATUS: u8 = 0x14;
+    pub const GET_DEVICE_ERRORS: u8 = 0x17;
+    pub const CLEAR_DEVICE_ERRORS: u8 = 0x07;
+    pub const GET_STATS: u8 = 0x10;
+    pub const RESET_STATS: u8 = 0x00;
+}
+
+/// Maximum number of fixed argument bytes of a command
+const MAX_HEAD_LEN: usize = 8;
+
+/// Command sent to the modem within a single transaction, see chapter 13.
+/// The arguments are kept as raw values, use the types in this module to encode them.
+/// Execute a command using SX126x::execute
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Command<'a> {
+    SetSleep {
+        sleep_config: u8,
+    },
+    SetStandby {
+        standby_config: u8,
+    },
+    SetFs,
+    SetTx {
+        /// Timeout in steps of 15.625 μs, 24 bits
+        timeout: u32,
+    },
+    SetRx {
+        /// Timeout in steps of 15.625 μs, 24 bits
+        timeout: u32,
+    },
+    StopTimerOnPreamble {
+        enable: bool,
+    },
+    SetRxDutyCycle {
+        rx_period: u32,
+        sleep_period: u32,
+    },
+    SetCad,
+    SetTxContinuousWave,
+    SetTxInfinitePreamble,
+    SetRegulatorMode {
+        mode: u8,
+    },
+    Calibrate {
+        calib_param: u8,
+    },
+    CalibrateImage {
+        freq1: u8,
+        freq2: u8,
+    },
+    SetPaConfig {
+        pa_config: [u8; 4],
+    },
+    SetRxTxFallbackMode {
+        mode: u8,
+    },
+    WriteRegister {
+        addr: u16,
+        data: &'a [u8],
+    },
+    ReadRegister {
+        addr: u16,
+    },
+    WriteBuffer {
+        offset: u8,
+        data: &'a [u8],
+    },
+    ReadBuffer {
+        offset: u8,
+    },
+    SetDioIrqParams {
+        irq_mask: u16,
+        dio1_mask: u16,
+        dio2_mask: u16,
+        dio3_mask: u16,
+    },
+    GetIrqStatus,
+    ClearIrqStatus {
+        mask: u16,
+    },
+    SetDio2AsRfSwitchCtrl {
+        enable: bool,
+    },
+    SetDio3AsTcxoCtrl {
+        tcxo_voltage: u8,
+        /// Delay in steps of 15.625 μs, 24 bits
+        tcxo_delay: u32,
+    },
+    SetRfFrequency {
+        rf_freq: u32,
+    },
+    SetPacketType {
+        packet_type: u8,
+    },
+    GetPacketType,
+    SetTxParams {
+        power: i8,
+        ramp_time: u8,
+    },
+    SetModulationParams {
+        params: &'a [u8],
+    },
+    SetPacketParams {
+        params: &'a [u8],
+    },
+    SetCadParams {
+        params: [u8; 7],
+    },
+    SetBufferBaseAddress {
+        tx_base_addr: u8,
+        rx_base_addr: u8,
+    },
+    SetLoRaSymbNumTimeout {
+        symb_num: u8,
+    },
+    GetStatus,
+    GetRssiInst,
+    GetRxBufferStatus,
+    GetPacketStatus,
+    GetDeviceErrors,
+    ClearDeviceErrors,
+    GetStats,
+    ResetStats,
+    /// Any command not covered by the other variants. If `response_len` is
+    /// not zero, the response is read after the status byte, like for the Get commands
+    Other {
+        opcode: u8,
+        args: &'a [u8],
+        response_len: usize,
+    },
+}
+
+/// Encoded arguments of a command, sent after the opcode
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Args<'a> {
+    head: [u8; MAX_HEAD_LEN],
+    head_len: usize,
+    data: &'a [u8],
+}
+
+impl<'a> Args<'a> {
+    fn new(head: &[u8], data: &'a [u8]) -> Self {
+        let mut args = Self {
+            head: [0; MAX_HEAD_LEN],
+            head_len: head.len(),
+            data,
+        };
+        args.head[..head.len()].copy_from_slice(head);
+        args
+    }
+
+    /// Fixed size arguments
+    pub fn head(&self) -> &[u8] {
+        &self.head[..self.head_len]
+    }
+
+    /// Variable size arguments, such as the data written to a register. Sent after the head
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Total number of argument bytes
+    pub fn len(&self) -> usize {
+        self.head_len + self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Encode a 24 bit value
+fn u24(val: u32) -> [u8; 3] {
+    let bytes = val.to_be_bytes();
+    [bytes[1], bytes[2], bytes[3]]
+}
+
+impl<'a> Command<'a> {
+    pub fn opcode(&self) -> u8 {
+        use self::opcode::*;
+        use Command::*;
+
+        match *self {
+            SetSleep { .. } => SET_SLEEP,
+            SetStandby { .. } => SET_STANDBY,
+            SetFs => SET_FS,
+            SetTx { .. } => SET_TX,
+            SetRx { .. } => SET_RX,
+            StopTimerOnPreamble { .. } => STOP_TIMER_ON_PREAMBLE,
+            SetRxDutyCycle { .. } => SET_RX_DUTY_CYCLE,
+            SetCad => SET_CAD,
+            SetTxContinuousWave => SET_TX_CONTINUOUS_WAVE,
+            SetTxInfinitePreamble => SET_TX_INFINITE_PREAMBLE,
+            SetRegulatorMode { .. } => SET_REGULATOR_MODE,
+            Calibrate { .. } => CALIBRATE,
+            CalibrateImage { .. } => CALIBRATE_IMAGE,
+            SetPaConfig { .. } => SET_PA_CONFIG,
+            SetRxTxFallbackMode { .. } => SET_RX_TX_FALLBACK_MODE,
+            WriteRegister { .. } => WRITE_REGISTER,
+            ReadRegister { .. } => READ_REGISTER,
+            WriteBuffer { .. } => WRITE_BUFFER,
+            ReadBuffer { .. } => READ_BUFFER,
+            SetDioIrqParams { .. } => SET_DIO_IRQ_PARAMS,
+            GetIrqStatus => GET_IRQ_STATUS,
+            ClearIrqStatus { .. } => CLEAR_IRQ_STATUS,
+            SetDio2AsRfSwitchCtrl { .. } => SET_DIO2_AS_RF_SWITCH_CTRL,
+            SetDio3AsTcxoCtrl { .. } => SET_DIO3_AS_TCXO_CTRL,
+            SetRfFrequency { .. } => SET_RF_FREQUENCY,
+            SetPacketType { .. } => SET_PACKET_TYPE,
+            GetPacketType => GET_PACKET_TYPE,
+            SetTxParams { .. } => SET_TX_PARAMS,
+            SetModulationParams { .. } => SET_MODULATION_PARAMS,
+            SetPacketParams { .. } => SET_PACKET_PARAMS,
+            SetCadParams { .. } => SET_CAD_PARAMS,
+            SetBufferBaseAddress { .. } => SET_BUFFER_BASE_ADDRESS,
+            SetLoRaSymbNumTimeout { .. } => SET_LORA_SYMB_NUM_TIMEOUT,
+            GetStatus => GET_STATUS,
+            GetRssiInst => GET_RSSI_INST,
+            GetRxBufferStatus => GET_RX_BUFFER_STATUS,
+            GetPacketStatus => GET_PACKET_STATUS,
+            GetDeviceErrors => GET_DEVICE_ERRORS,
+            ClearDeviceErrors => CLEAR_DEVICE_ERRORS,
+            GetStats => GET_STATS,
+            ResetStats => RESET_STATS,
+            Other { opcode, .. } => opcode,
+        }
+    }
+
+    /// Encode the arguments sent after the opcode. The NOP bytes sent
+    /// while reading the response are not included
+    pub fn args(&self) -> Args<'a> {
+        use Command::*;
+
+        match *self {
+            SetSleep { sleep_config } => Args::new(&[sleep_config], &[]),
+            SetStandby { standby_config } => Args::new(&[standby_config], &[]),
+            SetTx { timeout } | SetRx { timeout } => Args::new(&u24(timeout), &[]),
+            StopTimerOnPreamble { enable } => Args::new(&[enable as u8], &[]),
+            SetRxDutyCycle {
+                rx_period,
+                sleep_period,
+            } => {
+                let (rx_period, sleep_period) = (u24(rx_period), u24(sleep_period));
+                Args::new(
+                    &[
+                        rx_period[0],
+                        rx_period[1],
+                        rx_period[2],
+                        sleep_period[0],
+                        sleep_period[1],
+                        sleep_period[2],
+                    ],
+                    &[],
+                )
+            }
+            SetRegulatorMode { mode } => Args::new(&[mode], &[]),
+            Calibrate { calib_param } => Args::new(&[calib_param], &[]),
+            CalibrateImage { freq1, freq2 } => Args::new(&[freq1, freq2], &[]),
+            SetPaConfig { pa_config } => Args::new(&pa_config, &[]),
+            SetRxTxFallbackMode { mode } => Args::new(&[mode], &[]),
+            WriteRegister { addr, data } => Args::new(&addr.to_be_bytes(), data),
+            ReadRegister { addr } => Args::new(&addr.to_be_bytes(), &[]),
+            WriteBuffer { offset, data } => Args::new(&[offset], data),
+            ReadBuffer { offset } => Args::new(&[offset], &[]),
+            SetDioIrqParams {
+                irq_mask,
+                dio1_mask,
+                dio2_mask,
+                dio3_mask,
+            } => {
+                let masks = [irq_mask, dio1_mask, dio2_mask, dio3_mask];
+                let mut head = [0; 8];
+                for (bytes, mask) in head.chunks_mut(2).zip(masks.iter()) {
+                    bytes.copy_from_slice(&mask.to_be_bytes());
+                }
+                Args::new(&head, &[])
+            }
+            ClearIrqStatus { mask } => Args::new(&mask.to_be_bytes(), &[]),
+            SetDio2AsRfSwitchCtrl { enable } => Args::new(&[enable as u8], &[]),
+            SetDio3AsTcxoCtrl {
+                tcxo_voltage,
+                tcxo_delay,
+            } => {
+                let tcxo_delay = u24(tcxo_delay);
+                Args::new(
+                    &[tcxo_voltage, tcxo_delay[0], tcxo_delay[1], tcxo_delay[2]],
+                    &[],
+                )
+            }
+            SetRfFrequency { rf_freq } => Args::new(&rf_freq.to_be_bytes(), &[]),
+            SetPacketType { packet_type } => Args::new(&[packet_type], &[]),
+            SetTxParams { power, ramp_time } => Args::new(&[power as u8, ramp_time], &[]),
+            SetModulationParams { params } | SetPacketParams { params } => Args::new(&[], params),
+            SetCadParams { params } => Args::new(&params, &[]),
+            SetBufferBaseAddress {
+                tx_base_addr,
+                rx_base_addr,
+            } => Args::new(&[tx_base_addr, rx_base_addr], &[]),
+            SetLoRaSymbNumTimeout { symb_num } => Args::new(&[symb_num], &[]),
+            // 13.5.6 and 13.6.2: These commands are followed by zeroed bytes
+            ClearDeviceErrors => Args::new(&[0; 2], &[]),
+            ResetStats => Args::new(&[0; 6], &[]),
+            Other { args, .. } => Args::new(&[], args),
+            SetFs
+            | SetCad
+            | SetTxContinuousWave
+            | SetTxInfinitePreamble
+            | GetIrqStatus
+            | GetPacketType
+            | GetStatus
+            | GetRssiInst
+            | GetRxBufferStatus
+            | GetPacketStatus
+            | GetDeviceErrors
+            | GetStats => Args::new(&[], &[]),
+        }
+    }
+
+    /// Number of response bytes returned by the modem. None for ReadRegister and ReadBuffer,
+    /// which return as many bytes as are read
+    pub fn response_len(&self) -> Option<usize> {
+        use Command::*;
+
+        match *self {
+            GetStatus | GetPacketType | GetRssiInst => Some(1),
+            GetIrqStatus | GetRxBufferStatus | GetDeviceErrors => Some(2),
+            GetPacketStatus => Some(3),
+            GetStats => Some(6),
+            ReadRegister { .. } | ReadBuffer { .. } => None,
+            Other { response_len, .. } => Some(response_len),
+            _ => Some(0),
+        }
+    }
+
+    /// Position of the first response byte within the transaction. The bytes
+    /// between the arguments and the response are sent as NOP
+    pub fn response_offset(&self) -> usize {
+        match *self {
+            // 13.5.1: The status is returned in the byte following the opcode
+            Command::GetStatus => 1,
+            // Other commands with a response return the status byte, followed by the response
+            _ if self.response_len() != Some(0) => 2 + self.args().len(),
+            _ => 1 + self.args().len(),
+        }
+    }
+}
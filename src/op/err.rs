@@ -68,3 +68,34 @@ impl DeviceErrors {
         (self.inner & 1 << 8) > 0
     }
 }
+
+/// Error decoding raw parameters: a field holds a value that does not
+/// correspond to any valid setting
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InvalidValue {
+    /// Name of the field
+    pub field: &'static str,
+    pub value: u8,
+}
+
+/// Error choosing PA settings: the chip variant cannot reach the requested output power
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UnsupportedOutputPower {
+    pub variant: super::Variant,
+    pub dbm: i8,
+}
+
+/// Error setting the GFSK bit rate: the modem supports bit rates from 600 b/s up to 300 kb/s
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UnsupportedBitrate {
+    pub bitrate: u32,
+}
+
+/// Decode a boolean flag, which should be either 0 or 1
+pub(crate) fn try_bool(field: &'static str, value: u8) -> Result<bool, InvalidValue> {
+    match value {
+        0x00 => Ok(false),
+        0x01 => Ok(true),
+        value => Err(InvalidValue { field, value }),
+    }
+}
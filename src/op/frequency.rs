@@ -0,0 +1,120 @@
+/// Frequency of the crystal oscillator found on most SX126x modules
+pub const XTAL_HZ: u32 = 32_000_000;
+
+/// Value for SetRfFrequency of an RF frequency in Hz, given the XTAL frequency in Hz,
+/// rounded to the nearest PLL step.
+/// 13.4.1: RFfreq = RFfrequency * 2^25 / Fxtal
+pub const fn rf_freq_from_hz(hz: u32, xtal_hz: u32) -> u32 {
+    let xtal_hz = xtal_hz as u64;
+    ((((hz as u64) << 25) + xtal_hz / 2) / xtal_hz) as u32
+}
+
+/// RF frequency in Hz of a SetRfFrequency value, given the XTAL frequency in Hz,
+/// rounded to the nearest Hz.
+/// 13.4.1: RFfrequency = RFfreq * Fxtal / 2^25
+pub const fn hz_from_rf_freq(rf_freq: u32, xtal_hz: u32) -> u32 {
+    ((rf_freq as u64 * xtal_hz as u64 + (1 << 24)) >> 25) as u32
+}
+
+/// RF frequency, holding both the frequency in Hz and the
+/// value passed to SetRfFrequency
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Frequency {
+    hz: u32,
+    rf_freq: u32,
+}
+
+impl From<Frequency> for [u8; 4] {
+    fn from(frequency: Frequency) -> Self {
+        frequency.rf_freq.to_be_bytes()
+    }
+}
+
+impl Frequency {
+    /// Frequency in Hz, using an XTAL of XTAL_HZ
+    pub const fn from_hz(hz: u32) -> Self {
+        Self::from_hz_with_xtal(hz, XTAL_HZ)
+    }
+
+    /// Frequency in Hz, using an XTAL of `xtal_hz` Hz
+    pub const fn from_hz_with_xtal(hz: u32, xtal_hz: u32) -> Self {
+        Self {
+            hz,
+            rf_freq: rf_freq_from_hz(hz, xtal_hz),
+        }
+    }
+
+    /// Frequency from the value passed to SetRfFrequency, using an XTAL of `xtal_hz` Hz
+    pub const fn from_rf_freq(rf_freq: u32, xtal_hz: u32) -> Self {
+        Self {
+            hz: hz_from_rf_freq(rf_freq, xtal_hz),
+            rf_freq,
+        }
+    }
+
+    /// Frequency in Hz
+    pub fn hz(&self) -> u32 {
+        self.hz
+    }
+
+    /// Value passed to SetRfFrequency, in PLL steps of Fxtal / 2^25
+    pub fn rf_freq(&self) -> u32 {
+        self.rf_freq
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rf_freq() {
+        // 13.4.1: RFfreq = RFfrequency * 2^25 / 32 MHz
+        for &(hz, rf_freq) in &[
+            (868_000_000, 0x3640_0000),
+            (915_000_000, 0x3930_0000),
+            (433_000_000, 0x1B10_0000),
+            // 454216908.8 rounded to the nearest PLL step
+            (433_175_000, 454_216_909),
+        ] {
+            let frequency = Frequency::from_hz(hz);
+            assert_eq!(frequency.rf_freq(), rf_freq, "{} Hz", hz);
+            assert_eq!(frequency.hz(), hz);
+            assert_eq!(<[u8; 4]>::from(frequency), rf_freq.to_be_bytes());
+        }
+        assert_eq!(
+            Frequency::from_rf_freq(0x3640_0000, XTAL_HZ).hz(),
+            868_000_000
+        );
+    }
+
+    #[test]
+    fn rf_freq_round_trip() {
+        // One PLL step is 32 MHz / 2^25, slightly less than 1 Hz
+        for &hz in &[
+            150_000_000,
+            433_175_000,
+            868_100_000,
+            868_100_001,
+            902_300_123,
+            915_000_000,
+            959_999_999,
+            960_000_000,
+        ] {
+            let rf_freq = rf_freq_from_hz(hz, XTAL_HZ);
+            let round_trip = hz_from_rf_freq(rf_freq, XTAL_HZ);
+            assert!(
+                round_trip.abs_diff(hz) <= 1,
+                "{} Hz -> {} Hz",
+                hz,
+                round_trip
+            );
+        }
+
+        // Using a 26 MHz XTAL, a PLL step is slightly less than 0.8 Hz
+        let hz = 868_000_000;
+        let frequency = Frequency::from_hz_with_xtal(hz, 26_000_000);
+        let round_trip = hz_from_rf_freq(frequency.rf_freq(), 26_000_000);
+        assert!(round_trip.abs_diff(hz) <= 1);
+    }
+}
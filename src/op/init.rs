@@ -1,6 +1,96 @@
+use core::convert::TryFrom;
+
+use super::err::InvalidValue;
+
 #[repr(u8)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum StandbyConfig {
     StbyRc = 0x00,
     StbyXOSC = 0x01,
 }
+
+impl_try_from_u8!(StandbyConfig, [StbyRc, StbyXOSC]);
+
+/// Regulator used by the modem, see 13.1.11. With DcDc, the DC-DC converter
+/// is used in STDBY_XOSC, FS, RX and TX modes, which lowers the current consumption.
+/// It requires the inductor of the DC-DC converter to be fitted
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RegulatorMode {
+    /// Only use the LDO, the default
+    Ldo = 0x00,
+    DcDc = 0x01,
+}
+
+impl_try_from_u8!(RegulatorMode, [Ldo, DcDc]);
+
+/// Sleep mode configuration, see 13.1.1
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SleepConfig {
+    /// Retain the configuration while sleeping. If disabled (cold start),
+    /// the configuration is lost and the modem needs to be reconfigured on wake-up
+    pub warm_start: bool,
+    /// Wake up the modem when the RTC times out
+    pub rtc_wakeup: bool,
+}
+
+impl Default for SleepConfig {
+    fn default() -> Self {
+        Self {
+            warm_start: true,
+            rtc_wakeup: false,
+        }
+    }
+}
+
+impl From<SleepConfig> for u8 {
+    fn from(config: SleepConfig) -> Self {
+        (config.warm_start as u8) << 2 | (config.rtc_wakeup as u8)
+    }
+}
+
+impl TryFrom<u8> for SleepConfig {
+    type Error = InvalidValue;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        // Only bit 2 and bit 0 are used, the others are reserved
+        if value & !0x05 != 0 {
+            return Err(InvalidValue {
+                field: "SleepConfig",
+                value,
+            });
+        }
+        Ok(Self {
+            warm_start: value & 0x04 != 0,
+            rtc_wakeup: value & 0x01 != 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+
+    use super::{SleepConfig, StandbyConfig};
+
+    #[test]
+    fn sleep_config_round_trip() {
+        for &warm_start in [false, true].iter() {
+            for &rtc_wakeup in [false, true].iter() {
+                let config = SleepConfig {
+                    warm_start,
+                    rtc_wakeup,
+                };
+                assert_eq!(SleepConfig::try_from(u8::from(config)), Ok(config));
+            }
+        }
+        assert!(SleepConfig::try_from(0x02).is_err());
+    }
+
+    #[test]
+    fn standby_config_round_trip() {
+        for &config in [StandbyConfig::StbyRc, StandbyConfig::StbyXOSC].iter() {
+            assert_eq!(StandbyConfig::try_from(config as u8), Ok(config));
+        }
+    }
+}
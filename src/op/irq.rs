@@ -15,7 +15,7 @@ pub enum IrqMaskBit {
     All = 0xFFFF,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub struct IrqMask {
     inner: u16,
 }
@@ -68,6 +68,20 @@ impl From<u16> for IrqStatus {
     }
 }
 
+impl From<IrqStatus> for u16 {
+    fn from(status: IrqStatus) -> Self {
+        status.inner
+    }
+}
+
+impl From<IrqStatus> for IrqMask {
+    fn from(status: IrqStatus) -> Self {
+        Self {
+            inner: status.inner,
+        }
+    }
+}
+
 impl core::fmt::Debug for IrqStatus {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
@@ -128,4 +142,56 @@ impl IrqStatus {
     pub fn timeout(self) -> bool {
         (self.inner & IrqMaskBit::Timeout as u16) > 0
     }
+
+    /// Decode the most relevant event from the IRQ status.
+    /// Errors take precedence over RxDone, which is raised alongside them,
+    /// and completion events take precedence over intermediate ones
+    pub fn event(self) -> Option<RadioEvent> {
+        use RadioEvent::*;
+        if self.crc_err() {
+            Some(CrcError)
+        } else if self.header_error() {
+            Some(HeaderError)
+        } else if self.rx_done() {
+            Some(RxDone)
+        } else if self.tx_done() {
+            Some(TxDone)
+        } else if self.timeout() {
+            Some(Timeout)
+        } else if self.cad_done() {
+            Some(CadDone {
+                detected: self.cad_detected(),
+            })
+        } else if self.syncword_valid() {
+            Some(SyncWordValid)
+        } else if self.preamble_detected() {
+            Some(PreambleDetected)
+        } else {
+            None
+        }
+    }
+}
+
+/// Event signalled by the modem through its IRQ status
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RadioEvent {
+    /// Packet transmission completed
+    TxDone,
+    /// Packet received
+    RxDone,
+    /// Packet received with a wrong CRC
+    CrcError,
+    /// LoRa header received with a wrong CRC
+    HeaderError,
+    /// RX or TX timeout
+    Timeout,
+    /// Channel activity detection finished
+    CadDone {
+        /// Channel activity was detected
+        detected: bool,
+    },
+    /// Preamble detected
+    PreambleDetected,
+    /// Valid sync word detected
+    SyncWordValid,
 }
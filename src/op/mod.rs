@@ -1,20 +1,54 @@
 //! Defines the parameters used in every command detailed in chapter 13
+
+/// Implements TryFrom<u8> for an enum with explicit discriminants,
+/// failing with InvalidValue if the byte does not match any of the variants
+macro_rules! impl_try_from_u8 {
+    ($name:ident, [$($variant:ident),* $(,)?]) => {
+        impl core::convert::TryFrom<u8> for $name {
+            type Error = crate::op::InvalidValue;
+
+            fn try_from(value: u8) -> Result<Self, Self::Error> {
+                $(
+                    if value == Self::$variant as u8 {
+                        return Ok(Self::$variant);
+                    }
+                )*
+                Err(crate::op::InvalidValue {
+                    field: stringify!($name),
+                    value,
+                })
+            }
+        }
+    };
+}
+
+pub mod cad;
 pub mod calib;
+pub mod command;
 pub mod err;
+pub mod frequency;
 pub mod init;
 pub mod irq;
 pub mod modulation;
 pub mod packet;
+pub mod power;
 pub mod rxtx;
 pub mod status;
 pub mod tcxo;
+pub mod variant;
 
+pub use cad::*;
 pub use calib::*;
+pub use command::*;
 pub use err::*;
+pub use frequency::*;
 pub use init::*;
 pub use irq::*;
-pub use modulation::*;
-pub use packet::*;
+// Both modules define lora and gfsk submodules, which are re-exported under distinct names
+pub use modulation::{gfsk as gfsk_modulation, lora as lora_modulation, ModParams};
+pub use packet::{gfsk as gfsk_packet, lora as lora_packet, PacketParams, PacketType};
+pub use power::*;
 pub use rxtx::*;
 pub use status::*;
 pub use tcxo::*;
+pub use variant::*;
@@ -1,16 +1,33 @@
+/// Raw modulation params, see 13.4.5. Convert from and into
+/// LoraModParams or GfskModParams to access the fields
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct ModParams {
     inner: [u8; 8],
 }
 
-impl Into<[u8; 8]> for ModParams {
-    fn into(self) -> [u8; 8] {
-        self.inner
+impl From<ModParams> for [u8; 8] {
+    fn from(params: ModParams) -> Self {
+        params.inner
+    }
+}
+
+impl From<[u8; 8]> for ModParams {
+    fn from(inner: [u8; 8]) -> Self {
+        Self { inner }
     }
 }
 
 pub mod lora {
+    use core::convert::{TryFrom, TryInto};
+
     use super::ModParams;
-    #[derive(Copy, Clone)]
+    use crate::op::err::try_bool;
+    use crate::op::{InvalidValue, RxTxTimeout};
+
+    /// Number of symbols the modem listens for a preamble
+    /// in every receive window of the RX duty cycle
+    const DUTY_CYCLE_RX_SYMBOLS: u64 = 4;
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     #[repr(u8)]
     pub enum LoRaSpreadFactor {
         SF5 = 0x05,
@@ -23,7 +40,12 @@ pub mod lora {
         SF12 = 0x0C,
     }
 
-    #[derive(Copy, Clone)]
+    impl_try_from_u8!(
+        LoRaSpreadFactor,
+        [SF5, SF6, SF7, SF8, SF9, SF10, SF11, SF12]
+    );
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     #[repr(u8)]
     pub enum LoRaBandWidth {
         /// 7.81 kHz
@@ -48,7 +70,30 @@ pub mod lora {
         BW500 = 0x06,
     }
 
-    #[derive(Copy, Clone)]
+    impl_try_from_u8!(
+        LoRaBandWidth,
+        [BW7, BW10, BW15, BW20, BW31, BW41, BW62, BW125, BW250, BW500]
+    );
+
+    impl LoRaBandWidth {
+        /// Bandwidth in Hz
+        pub const fn hz(self) -> u32 {
+            match self {
+                Self::BW7 => 7_810,
+                Self::BW10 => 10_420,
+                Self::BW15 => 15_630,
+                Self::BW20 => 20_830,
+                Self::BW31 => 31_250,
+                Self::BW41 => 41_670,
+                Self::BW62 => 62_500,
+                Self::BW125 => 125_000,
+                Self::BW250 => 250_000,
+                Self::BW500 => 500_000,
+            }
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     #[repr(u8)]
     pub enum LoraCodingRate {
         CR4_5 = 0x01,
@@ -57,6 +102,9 @@ pub mod lora {
         CR4_8 = 0x04,
     }
 
+    impl_try_from_u8!(LoraCodingRate, [CR4_5, CR4_6, CR4_7, CR4_8]);
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     pub struct LoraModParams {
         spread_factor: LoRaSpreadFactor,
         bandwidth: LoRaBandWidth,
@@ -94,16 +142,53 @@ pub mod lora {
             self.low_dr_opt = low_dr_opt;
             self
         }
+
+        pub fn spread_factor(&self) -> LoRaSpreadFactor {
+            self.spread_factor
+        }
+
+        pub fn bandwidth(&self) -> LoRaBandWidth {
+            self.bandwidth
+        }
+
+        /// Duration of a single LoRa symbol in μs
+        pub fn symbol_time_us(&self) -> u32 {
+            ((1_000_000u64 << self.spread_factor as u8) / self.bandwidth.hz() as u64) as u32
+        }
+
+        /// Calculate the RX and sleep periods to pass to SX126x::set_rx_duty_cycle,
+        /// so that every packet sent with a preamble of `preamble_len` symbols is caught.
+        /// A preamble may start right after a receive window has started,
+        /// so the preamble should span at least one sleep period and two receive windows.
+        /// Returns None if the preamble is too short for a duty cycled receiver.
+        pub fn rx_duty_cycle_periods(
+            &self,
+            preamble_len: u16,
+        ) -> Option<(RxTxTimeout, RxTxTimeout)> {
+            let symbol_time = self.symbol_time_us() as u64;
+            let preamble_time = preamble_len as u64 * symbol_time;
+            let rx_period = DUTY_CYCLE_RX_SYMBOLS * symbol_time;
+            let sleep_period = preamble_time.checked_sub(2 * rx_period)?;
+            if sleep_period == 0 {
+                return None;
+            }
+            // The timeouts are 24-bit values in steps of 15.625 μs
+            const MAX_PERIOD_US: u64 = 0xFF_FFFF * 125 / 8;
+            Some((
+                RxTxTimeout::from_us(rx_period.min(MAX_PERIOD_US) as u32),
+                RxTxTimeout::from_us(sleep_period.min(MAX_PERIOD_US) as u32),
+            ))
+        }
     }
 
-    impl Into<ModParams> for LoraModParams {
-        fn into(self) -> ModParams {
+    impl From<LoraModParams> for ModParams {
+        fn from(params: LoraModParams) -> Self {
             ModParams {
                 inner: [
-                    self.spread_factor as u8,
-                    self.bandwidth as u8,
-                    self.coding_rate as u8,
-                    self.low_dr_opt as u8,
+                    params.spread_factor as u8,
+                    params.bandwidth as u8,
+                    params.coding_rate as u8,
+                    params.low_dr_opt as u8,
                     0x00,
                     0x00,
                     0x00,
@@ -112,4 +197,268 @@ pub mod lora {
             }
         }
     }
+
+    impl TryFrom<[u8; 8]> for LoraModParams {
+        type Error = InvalidValue;
+
+        fn try_from(raw: [u8; 8]) -> Result<Self, Self::Error> {
+            Ok(Self {
+                spread_factor: raw[0].try_into()?,
+                bandwidth: raw[1].try_into()?,
+                coding_rate: raw[2].try_into()?,
+                low_dr_opt: try_bool("low_dr_opt", raw[3])?,
+            })
+        }
+    }
+
+    impl TryFrom<ModParams> for LoraModParams {
+        type Error = InvalidValue;
+
+        fn try_from(params: ModParams) -> Result<Self, Self::Error> {
+            params.inner.try_into()
+        }
+    }
+}
+
+pub mod gfsk {
+    use core::convert::{TryFrom, TryInto};
+
+    use super::ModParams;
+    use crate::op::{hz_from_rf_freq, rf_freq_from_hz, InvalidValue, UnsupportedBitrate, XTAL_HZ};
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum GfskPulseShape {
+        /// No filter applied
+        None = 0x00,
+        /// Gaussian BT 0.3
+        BT0_3 = 0x08,
+        /// Gaussian BT 0.5
+        BT0_5 = 0x09,
+        /// Gaussian BT 0.7
+        BT0_7 = 0x0A,
+        /// Gaussian BT 1
+        BT1_0 = 0x0B,
+    }
+
+    impl_try_from_u8!(GfskPulseShape, [None, BT0_3, BT0_5, BT0_7, BT1_0]);
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum GfskBandWidth {
+        /// 4.8 kHz DSB
+        BW4800 = 0x1F,
+        /// 5.8 kHz DSB
+        BW5800 = 0x17,
+        /// 7.3 kHz DSB
+        BW7300 = 0x0F,
+        /// 9.7 kHz DSB
+        BW9700 = 0x1E,
+        /// 11.7 kHz DSB
+        BW11700 = 0x16,
+        /// 14.6 kHz DSB
+        BW14600 = 0x0E,
+        /// 19.5 kHz DSB
+        BW19500 = 0x1D,
+        /// 23.4 kHz DSB
+        BW23400 = 0x15,
+        /// 29.3 kHz DSB
+        BW29300 = 0x0D,
+        /// 39.0 kHz DSB
+        BW39000 = 0x1C,
+        /// 46.9 kHz DSB
+        BW46900 = 0x14,
+        /// 58.6 kHz DSB
+        BW58600 = 0x0C,
+        /// 78.2 kHz DSB
+        BW78200 = 0x1B,
+        /// 93.8 kHz DSB
+        BW93800 = 0x13,
+        /// 117.3 kHz DSB
+        BW117300 = 0x0B,
+        /// 156.2 kHz DSB
+        BW156200 = 0x1A,
+        /// 187.2 kHz DSB
+        BW187200 = 0x12,
+        /// 234.3 kHz DSB
+        BW234300 = 0x0A,
+        /// 312.0 kHz DSB
+        BW312000 = 0x19,
+        /// 373.6 kHz DSB
+        BW373600 = 0x11,
+        /// 467.0 kHz DSB
+        BW467000 = 0x09,
+    }
+
+    impl_try_from_u8!(
+        GfskBandWidth,
+        [
+            BW4800, BW5800, BW7300, BW9700, BW11700, BW14600, BW19500, BW23400, BW29300, BW39000,
+            BW46900, BW58600, BW78200, BW93800, BW117300, BW156200, BW187200, BW234300, BW312000,
+            BW373600, BW467000,
+        ]
+    );
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct GfskModParams {
+        /// Bit rate in bits per second
+        bitrate: u32,
+        pulse_shape: GfskPulseShape,
+        bandwidth: GfskBandWidth,
+        /// Frequency deviation in Hz
+        fdev: u32,
+    }
+
+    impl Default for GfskModParams {
+        fn default() -> Self {
+            Self {
+                bitrate: 50_000,
+                pulse_shape: GfskPulseShape::BT0_5,
+                bandwidth: GfskBandWidth::BW117300,
+                fdev: 25_000,
+            }
+        }
+    }
+
+    impl GfskModParams {
+        /// Set the bit rate in bits per second.
+        /// The modem supports bit rates from 600 b/s up to 300 kb/s, other bit rates are rejected
+        pub fn set_bitrate(mut self, bitrate: u32) -> Result<Self, UnsupportedBitrate> {
+            if !(600..=300_000).contains(&bitrate) {
+                return Err(UnsupportedBitrate { bitrate });
+            }
+            self.bitrate = bitrate;
+            Ok(self)
+        }
+
+        pub fn set_pulse_shape(mut self, pulse_shape: GfskPulseShape) -> Self {
+            self.pulse_shape = pulse_shape;
+            self
+        }
+
+        /// Set the RX bandwidth. It should be chosen so that
+        /// `bandwidth >= 2 * fdev + bitrate` (plus the expected frequency error)
+        pub fn set_bandwidth(mut self, bandwidth: GfskBandWidth) -> Self {
+            self.bandwidth = bandwidth;
+            self
+        }
+
+        /// Set the frequency deviation in Hz
+        pub fn set_fdev(mut self, fdev: u32) -> Self {
+            self.fdev = fdev;
+            self
+        }
+    }
+
+    impl From<GfskModParams> for ModParams {
+        fn from(params: GfskModParams) -> Self {
+            // 13.4.5.1: BR = 32 * Fxtal / bit rate
+            let br = ((32 * XTAL_HZ as u64) / params.bitrate as u64) as u32;
+            let br = br.to_be_bytes();
+            // 13.4.5.1: Fdev = (Frequency deviation * 2^25) / Fxtal, rounded to the nearest step
+            let fdev = rf_freq_from_hz(params.fdev, XTAL_HZ).to_be_bytes();
+
+            ModParams {
+                inner: [
+                    br[1],
+                    br[2],
+                    br[3],
+                    params.pulse_shape as u8,
+                    params.bandwidth as u8,
+                    fdev[1],
+                    fdev[2],
+                    fdev[3],
+                ],
+            }
+        }
+    }
+
+    impl TryFrom<[u8; 8]> for GfskModParams {
+        type Error = InvalidValue;
+
+        fn try_from(raw: [u8; 8]) -> Result<Self, Self::Error> {
+            let br = u32::from_be_bytes([0, raw[0], raw[1], raw[2]]) as u64;
+            if br == 0 {
+                return Err(InvalidValue {
+                    field: "bitrate",
+                    value: 0,
+                });
+            }
+            let fdev = u32::from_be_bytes([0, raw[5], raw[6], raw[7]]);
+            Ok(Self {
+                // 13.4.5.1: bit rate = 32 * Fxtal / BR, rounded to the nearest b/s
+                bitrate: ((32 * XTAL_HZ as u64 + br / 2) / br) as u32,
+                pulse_shape: raw[3].try_into()?,
+                bandwidth: raw[4].try_into()?,
+                // 13.4.5.1: Frequency deviation = Fdev * Fxtal / 2^25, rounded to the nearest Hz
+                fdev: hz_from_rf_freq(fdev, XTAL_HZ),
+            })
+        }
+    }
+
+    impl TryFrom<ModParams> for GfskModParams {
+        type Error = InvalidValue;
+
+        fn try_from(params: ModParams) -> Result<Self, Self::Error> {
+            params.inner.try_into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+
+    use super::gfsk::{GfskBandWidth, GfskModParams, GfskPulseShape};
+    use super::lora::{LoRaBandWidth, LoRaSpreadFactor, LoraCodingRate, LoraModParams};
+    use crate::op::UnsupportedBitrate;
+
+    #[test]
+    fn lora_mod_params_round_trip() {
+        let params = LoraModParams::default()
+            .set_spread_factor(LoRaSpreadFactor::SF12)
+            .set_bandwidth(LoRaBandWidth::BW41)
+            .set_coding_rate(LoraCodingRate::CR4_8)
+            .set_low_dr_opt(true);
+        let raw = <[u8; 8]>::from(super::ModParams::from(params));
+        assert_eq!(raw, [0x0C, 0x0A, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(LoraModParams::try_from(raw), Ok(params));
+    }
+
+    #[test]
+    fn gfsk_mod_params_round_trip() {
+        let params = GfskModParams::default()
+            .set_bitrate(100_000)
+            .unwrap()
+            .set_pulse_shape(GfskPulseShape::BT1_0)
+            .set_bandwidth(GfskBandWidth::BW234300)
+            .set_fdev(50_000);
+        let raw = <[u8; 8]>::from(super::ModParams::from(params));
+        assert_eq!(GfskModParams::try_from(raw), Ok(params));
+        assert_eq!(
+            GfskModParams::try_from(<[u8; 8]>::from(super::ModParams::from(
+                GfskModParams::default()
+            ))),
+            Ok(GfskModParams::default())
+        );
+    }
+
+    #[test]
+    fn unsupported_bitrate() {
+        for &bitrate in &[0, 599, 300_001, u32::MAX] {
+            assert_eq!(
+                GfskModParams::default().set_bitrate(bitrate),
+                Err(UnsupportedBitrate { bitrate })
+            );
+        }
+        assert!(GfskModParams::default().set_bitrate(600).is_ok());
+        assert!(GfskModParams::default().set_bitrate(300_000).is_ok());
+    }
+
+    #[test]
+    fn invalid_mod_params() {
+        let raw = [0x0D, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00];
+        assert!(LoraModParams::try_from(raw).is_err());
+        assert!(GfskModParams::try_from([0; 8]).is_err());
+    }
 }
@@ -1,25 +1,39 @@
 #[repr(u8)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PacketType {
     GFSK = 0x00,
     LoRa = 0x01,
 }
 
+impl_try_from_u8!(PacketType, [GFSK, LoRa]);
+
+/// Raw packet params, see 13.4.6. Convert from and into
+/// LoRaPacketParams or GfskPacketParams to access the fields
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct PacketParams {
     inner: [u8; 9],
 }
 
-impl Into<[u8; 9]> for PacketParams {
-    fn into(self) -> [u8; 9] {
-        self.inner
+impl From<PacketParams> for [u8; 9] {
+    fn from(params: PacketParams) -> Self {
+        params.inner
+    }
+}
+
+impl From<[u8; 9]> for PacketParams {
+    fn from(inner: [u8; 9]) -> Self {
+        Self { inner }
     }
 }
 
 pub mod lora {
+    use core::convert::{TryFrom, TryInto};
+
     use super::PacketParams;
+    use crate::op::InvalidValue;
 
     #[repr(u8)]
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     pub enum LoRaHeaderType {
         /// Variable length packet (explicit header)
         VarLen = 0x00,
@@ -27,8 +41,10 @@ pub mod lora {
         FixedLen = 0x01,
     }
 
+    impl_try_from_u8!(LoRaHeaderType, [VarLen, FixedLen]);
+
     #[repr(u8)]
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     pub enum LoRaCrcType {
         /// CRC off
         CrcOff = 0x00,
@@ -36,8 +52,10 @@ pub mod lora {
         CrcOn = 0x01,
     }
 
+    impl_try_from_u8!(LoRaCrcType, [CrcOff, CrcOn]);
+
     #[repr(u8)]
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     pub enum LoRaInvertIq {
         /// Standard IQ setup
         Standard = 0x00,
@@ -45,6 +63,9 @@ pub mod lora {
         Inverted = 0x01,
     }
 
+    impl_try_from_u8!(LoRaInvertIq, [Standard, Inverted]);
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     pub struct LoRaPacketParams {
         /// preamble length: number of symbols sent as preamble
         /// The preamble length is a 16-bit value which represents
@@ -64,18 +85,18 @@ pub mod lora {
         invert_iq: LoRaInvertIq,
     }
 
-    impl Into<PacketParams> for LoRaPacketParams {
-        fn into(self) -> PacketParams {
-            let preamble_len = self.preamble_len.to_be_bytes();
+    impl From<LoRaPacketParams> for PacketParams {
+        fn from(params: LoRaPacketParams) -> Self {
+            let preamble_len = params.preamble_len.to_be_bytes();
 
             PacketParams {
                 inner: [
                     preamble_len[0],
                     preamble_len[1],
-                    self.header_type as u8,
-                    self.payload_len,
-                    self.crc_type as u8,
-                    self.invert_iq as u8,
+                    params.header_type as u8,
+                    params.payload_len,
+                    params.crc_type as u8,
+                    params.invert_iq as u8,
                     0x00,
                     0x00,
                     0x00,
@@ -84,6 +105,28 @@ pub mod lora {
         }
     }
 
+    impl TryFrom<[u8; 9]> for LoRaPacketParams {
+        type Error = InvalidValue;
+
+        fn try_from(raw: [u8; 9]) -> Result<Self, Self::Error> {
+            Ok(Self {
+                preamble_len: u16::from_be_bytes([raw[0], raw[1]]),
+                header_type: raw[2].try_into()?,
+                payload_len: raw[3],
+                crc_type: raw[4].try_into()?,
+                invert_iq: raw[5].try_into()?,
+            })
+        }
+    }
+
+    impl TryFrom<PacketParams> for LoRaPacketParams {
+        type Error = InvalidValue;
+
+        fn try_from(params: PacketParams) -> Result<Self, Self::Error> {
+            params.inner.try_into()
+        }
+    }
+
     impl Default for LoRaPacketParams {
         fn default() -> Self {
             Self {
@@ -123,3 +166,259 @@ pub mod lora {
         }
     }
 }
+
+pub mod gfsk {
+    use core::convert::{TryFrom, TryInto};
+
+    use super::PacketParams;
+    use crate::op::err::try_bool;
+    use crate::op::InvalidValue;
+
+    #[repr(u8)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub enum GfskPreambleDetectorLength {
+        /// Preamble detector off
+        Off = 0x00,
+        /// Preamble detector length 8 bits
+        Bits8 = 0x04,
+        /// Preamble detector length 16 bits
+        Bits16 = 0x05,
+        /// Preamble detector length 24 bits
+        Bits24 = 0x06,
+        /// Preamble detector length 32 bits
+        Bits32 = 0x07,
+    }
+
+    impl_try_from_u8!(
+        GfskPreambleDetectorLength,
+        [Off, Bits8, Bits16, Bits24, Bits32]
+    );
+
+    #[repr(u8)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub enum GfskAddrComp {
+        /// Address filtering disabled
+        Off = 0x00,
+        /// Filtering on node address
+        Node = 0x01,
+        /// Filtering on node and broadcast addresses
+        NodeBroadcast = 0x02,
+    }
+
+    impl_try_from_u8!(GfskAddrComp, [Off, Node, NodeBroadcast]);
+
+    #[repr(u8)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub enum GfskHeaderType {
+        /// Fixed length packet, the packet length is known on both sides
+        /// and is not added to the packet
+        FixedLen = 0x00,
+        /// Variable length packet, the packet length is added to the packet
+        VarLen = 0x01,
+    }
+
+    impl_try_from_u8!(GfskHeaderType, [FixedLen, VarLen]);
+
+    #[repr(u8)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub enum GfskCrcType {
+        /// No CRC
+        CrcOff = 0x01,
+        /// CRC computed on 1 byte
+        Crc1Byte = 0x00,
+        /// CRC computed on 2 bytes
+        Crc2Byte = 0x02,
+        /// CRC computed on 1 byte and inverted
+        Crc1ByteInv = 0x04,
+        /// CRC computed on 2 bytes and inverted
+        Crc2ByteInv = 0x06,
+    }
+
+    impl_try_from_u8!(
+        GfskCrcType,
+        [CrcOff, Crc1Byte, Crc2Byte, Crc1ByteInv, Crc2ByteInv]
+    );
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct GfskPacketParams {
+        /// Preamble length: number of bits sent as preamble
+        preamble_len: u16, // 1, 2
+        /// Preamble detector length
+        preamble_detector_len: GfskPreambleDetectorLength, // 3
+        /// Sync word length in bits, from 0 to 64
+        sync_word_len: u8, // 4
+        /// Address comparison mode
+        addr_comp: GfskAddrComp, // 5
+        /// Fixed or variable length packet
+        header_type: GfskHeaderType, // 6
+        /// Size of the payload (in bytes) to transmit or maximum size of the
+        /// payload that the receiver can accept.
+        payload_len: u8, // 7
+        /// CRC type
+        crc_type: GfskCrcType, // 8
+        /// Whitening enabled
+        whitening: bool, // 9
+    }
+
+    impl From<GfskPacketParams> for PacketParams {
+        fn from(params: GfskPacketParams) -> Self {
+            let preamble_len = params.preamble_len.to_be_bytes();
+
+            PacketParams {
+                inner: [
+                    preamble_len[0],
+                    preamble_len[1],
+                    params.preamble_detector_len as u8,
+                    params.sync_word_len,
+                    params.addr_comp as u8,
+                    params.header_type as u8,
+                    params.payload_len,
+                    params.crc_type as u8,
+                    params.whitening as u8,
+                ],
+            }
+        }
+    }
+
+    impl TryFrom<[u8; 9]> for GfskPacketParams {
+        type Error = InvalidValue;
+
+        fn try_from(raw: [u8; 9]) -> Result<Self, Self::Error> {
+            if raw[3] > 64 {
+                return Err(InvalidValue {
+                    field: "sync_word_len",
+                    value: raw[3],
+                });
+            }
+            Ok(Self {
+                preamble_len: u16::from_be_bytes([raw[0], raw[1]]),
+                preamble_detector_len: raw[2].try_into()?,
+                sync_word_len: raw[3],
+                addr_comp: raw[4].try_into()?,
+                header_type: raw[5].try_into()?,
+                payload_len: raw[6],
+                crc_type: raw[7].try_into()?,
+                whitening: try_bool("whitening", raw[8])?,
+            })
+        }
+    }
+
+    impl TryFrom<PacketParams> for GfskPacketParams {
+        type Error = InvalidValue;
+
+        fn try_from(params: PacketParams) -> Result<Self, Self::Error> {
+            params.inner.try_into()
+        }
+    }
+
+    impl Default for GfskPacketParams {
+        fn default() -> Self {
+            Self {
+                preamble_len: 0x0020,
+                preamble_detector_len: GfskPreambleDetectorLength::Bits16,
+                sync_word_len: 0x10,
+                addr_comp: GfskAddrComp::Off,
+                header_type: GfskHeaderType::VarLen,
+                payload_len: 0xFF,
+                crc_type: GfskCrcType::Crc2ByteInv,
+                whitening: false,
+            }
+        }
+    }
+
+    impl GfskPacketParams {
+        pub fn set_preamble_len(mut self, preamble_len: u16) -> Self {
+            self.preamble_len = preamble_len;
+            self
+        }
+
+        pub fn set_preamble_detector_len(
+            mut self,
+            preamble_detector_len: GfskPreambleDetectorLength,
+        ) -> Self {
+            self.preamble_detector_len = preamble_detector_len;
+            self
+        }
+
+        /// Set the sync word length in bits. The sync word itself is
+        /// configured using SX126x::set_gfsk_sync_word
+        pub fn set_sync_word_len(mut self, sync_word_len: u8) -> Self {
+            debug_assert!(sync_word_len <= 64);
+            self.sync_word_len = sync_word_len;
+            self
+        }
+
+        pub fn set_addr_comp(mut self, addr_comp: GfskAddrComp) -> Self {
+            self.addr_comp = addr_comp;
+            self
+        }
+
+        pub fn set_header_type(mut self, header_type: GfskHeaderType) -> Self {
+            self.header_type = header_type;
+            self
+        }
+
+        pub fn set_payload_len(mut self, payload_len: u8) -> Self {
+            self.payload_len = payload_len;
+            self
+        }
+
+        pub fn set_crc_type(mut self, crc_type: GfskCrcType) -> Self {
+            self.crc_type = crc_type;
+            self
+        }
+
+        pub fn set_whitening(mut self, whitening: bool) -> Self {
+            self.whitening = whitening;
+            self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+
+    use super::gfsk::{
+        GfskAddrComp, GfskCrcType, GfskHeaderType, GfskPacketParams, GfskPreambleDetectorLength,
+    };
+    use super::lora::{LoRaCrcType, LoRaHeaderType, LoRaInvertIq, LoRaPacketParams};
+    use super::PacketParams;
+
+    #[test]
+    fn lora_packet_params_round_trip() {
+        let params = LoRaPacketParams::default()
+            .set_preamble_len(0x0123)
+            .set_header_type(LoRaHeaderType::FixedLen)
+            .set_payload_len(42)
+            .set_crc_type(LoRaCrcType::CrcOn)
+            .set_invert_iq(LoRaInvertIq::Inverted);
+        let raw = <[u8; 9]>::from(PacketParams::from(params));
+        assert_eq!(LoRaPacketParams::try_from(raw), Ok(params));
+    }
+
+    #[test]
+    fn gfsk_packet_params_round_trip() {
+        let params = GfskPacketParams::default()
+            .set_preamble_len(0x0140)
+            .set_preamble_detector_len(GfskPreambleDetectorLength::Bits32)
+            .set_sync_word_len(64)
+            .set_addr_comp(GfskAddrComp::NodeBroadcast)
+            .set_header_type(GfskHeaderType::FixedLen)
+            .set_payload_len(200)
+            .set_crc_type(GfskCrcType::Crc1Byte)
+            .set_whitening(true);
+        let raw = <[u8; 9]>::from(PacketParams::from(params));
+        assert_eq!(GfskPacketParams::try_from(raw), Ok(params));
+    }
+
+    #[test]
+    fn invalid_packet_params() {
+        let mut raw = <[u8; 9]>::from(PacketParams::from(GfskPacketParams::default()));
+        raw[3] = 65;
+        assert!(GfskPacketParams::try_from(raw).is_err());
+        let mut raw = <[u8; 9]>::from(PacketParams::from(LoRaPacketParams::default()));
+        raw[5] = 0x02;
+        assert!(LoRaPacketParams::try_from(raw).is_err());
+    }
+}
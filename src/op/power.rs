@@ -0,0 +1,141 @@
+use super::{PaConfig, RampTime, TxParams, UnsupportedOutputPower, Variant};
+use crate::reg::OcpConfiguration;
+
+/// Optimal PA settings from table 13-21: output power in dBm,
+/// pa_duty_cycle, hp_max and the power passed to SetTxParams
+type PaTableEntry = (i8, u8, u8, i8);
+
+const SX1261_PA_TABLE: [PaTableEntry; 3] = [
+    (10, 0x01, 0x00, 13),
+    (14, 0x04, 0x00, 14),
+    (15, 0x06, 0x00, 14),
+];
+
+const SX1262_PA_TABLE: [PaTableEntry; 4] = [
+    (14, 0x02, 0x02, 22),
+    (17, 0x02, 0x03, 22),
+    (20, 0x03, 0x05, 22),
+    (22, 0x04, 0x07, 22),
+];
+
+const SX1268_PA_TABLE: [PaTableEntry; 4] = [
+    (14, 0x04, 0x06, 22),
+    (17, 0x02, 0x03, 22),
+    (20, 0x03, 0x05, 22),
+    (22, 0x04, 0x07, 22),
+];
+
+/// Target output power. Use OutputPower::pa_settings to get the
+/// settings to reach it using a specific chip variant
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OutputPower {
+    dbm: i8,
+    ramp_time: RampTime,
+}
+
+/// Settings to reach an output power, apply them using SX126x::set_output_power
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PaSettings {
+    pub pa_config: PaConfig,
+    pub tx_params: TxParams,
+    /// Over current protection level. Must be written after SetPaConfig,
+    /// which resets it to the default of the selected PA
+    pub ocp: OcpConfiguration,
+}
+
+impl OutputPower {
+    /// Target output power in dBm
+    pub const fn dbm(dbm: i8) -> Self {
+        Self {
+            dbm,
+            ramp_time: RampTime::Ramp200u,
+        }
+    }
+
+    /// Set power ramp time. Defaults to 200 μs
+    pub fn set_ramp_time(mut self, ramp_time: RampTime) -> Self {
+        self.ramp_time = ramp_time;
+        self
+    }
+
+    /// Choose the PA settings for `variant`. The PA is configured using the optimal settings
+    /// from table 13-21 for the lowest listed output power that is at least the target power,
+    /// and the power passed to SetTxParams is lowered by the difference.
+    /// Fails if `variant` cannot reach the target power
+    pub fn pa_settings(&self, variant: Variant) -> Result<PaSettings, UnsupportedOutputPower> {
+        let (table, ocp): (&[PaTableEntry], OcpConfiguration) = match variant {
+            Variant::SX1261 => (&SX1261_PA_TABLE, OcpConfiguration::from_ma(60)),
+            Variant::SX1262 | Variant::LLCC68 => (&SX1262_PA_TABLE, OcpConfiguration::from_ma(140)),
+            Variant::SX1268 => (&SX1268_PA_TABLE, OcpConfiguration::from_ma(140)),
+        };
+        let min_power = *variant.tx_power_range().start();
+        let unsupported = UnsupportedOutputPower {
+            variant,
+            dbm: self.dbm,
+        };
+        let &(dbm, pa_duty_cycle, hp_max, power) = table
+            .iter()
+            .find(|(dbm, ..)| *dbm >= self.dbm)
+            .ok_or(unsupported)?;
+        // Computed as i16, as the difference overflows an i8 for very low targets
+        let power_dbm = power as i16 - (dbm as i16 - self.dbm as i16);
+        if power_dbm < min_power as i16 {
+            return Err(unsupported);
+        }
+        let power_dbm = power_dbm as i8;
+        Ok(PaSettings {
+            pa_config: PaConfig::default()
+                .set_pa_duty_cycle(pa_duty_cycle)
+                .set_hp_max(hp_max)
+                .set_device_sel(variant.device_sel()),
+            tx_params: TxParams::default()
+                .set_power_dbm(power_dbm)
+                .set_ramp_time(self.ramp_time),
+            ocp,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pa_settings() {
+        let settings = OutputPower::dbm(22).pa_settings(Variant::SX1262).unwrap();
+        assert_eq!(
+            <[u8; 4]>::from(settings.pa_config),
+            [0x04, 0x07, 0x00, 0x01]
+        );
+        assert_eq!(<[u8; 2]>::from(settings.tx_params), [22, 0x04]);
+
+        // Lowered from the 14 dBm settings
+        let settings = OutputPower::dbm(12).pa_settings(Variant::SX1261).unwrap();
+        assert_eq!(
+            <[u8; 4]>::from(settings.pa_config),
+            [0x04, 0x00, 0x01, 0x01]
+        );
+        assert_eq!(<[u8; 2]>::from(settings.tx_params), [12, 0x04]);
+
+        // Lowest power reachable using the 14 dBm settings
+        let settings = OutputPower::dbm(-17).pa_settings(Variant::SX1262).unwrap();
+        assert_eq!(<[u8; 2]>::from(settings.tx_params), [-9i8 as u8, 0x04]);
+    }
+
+    #[test]
+    fn unsupported_output_power() {
+        for &(variant, dbm) in &[
+            (Variant::SX1261, 16),
+            (Variant::SX1262, 23),
+            (Variant::SX1262, -18),
+            (Variant::SX1268, i8::MIN),
+            (Variant::SX1261, i8::MIN),
+            (Variant::LLCC68, i8::MAX),
+        ] {
+            assert_eq!(
+                OutputPower::dbm(dbm).pa_settings(variant),
+                Err(UnsupportedOutputPower { variant, dbm })
+            );
+        }
+    }
+}
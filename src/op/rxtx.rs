@@ -1,11 +1,22 @@
-#[derive(Copy, Clone)]
+use core::convert::{TryFrom, TryInto};
+
+use super::err::InvalidValue;
+use super::packet::PacketType;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct RxTxTimeout {
     inner: [u8; 3],
 }
 
-impl Into<[u8; 3]> for RxTxTimeout {
-    fn into(self) -> [u8; 3] {
-        self.inner
+impl From<RxTxTimeout> for [u8; 3] {
+    fn from(timeout: RxTxTimeout) -> Self {
+        timeout.inner
+    }
+}
+
+impl From<[u8; 3]> for RxTxTimeout {
+    fn from(inner: [u8; 3]) -> Self {
+        Self { inner }
     }
 }
 
@@ -16,19 +27,35 @@ impl RxTxTimeout {
         let inner = [inner[2], inner[1], inner[0]];
         Self { inner }
     }
+
+    /// Create a timeout from a duration in μs, rounded down to steps of 15.625 μs
+    pub const fn from_us(us: u32) -> Self {
+        let inner = (us as u64 * 8 / 125) as u32;
+        let inner = inner.to_le_bytes();
+        let inner = [inner[2], inner[1], inner[0]];
+        Self { inner }
+    }
 }
 
+/// Create a timeout from a number of 15.625 μs steps. Only the lower 24 bits are used
 impl From<u32> for RxTxTimeout {
     fn from(val: u32) -> Self {
         let bytes = val.to_be_bytes();
         Self {
-            inner: [bytes[0], bytes[1], bytes[2]],
+            inner: [bytes[1], bytes[2], bytes[3]],
         }
     }
 }
 
+/// Number of 15.625 μs steps
+impl From<RxTxTimeout> for u32 {
+    fn from(timeout: RxTxTimeout) -> Self {
+        u32::from_be_bytes([0, timeout.inner[0], timeout.inner[1], timeout.inner[2]])
+    }
+}
+
 #[repr(u8)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum RampTime {
     /// 10us
     Ramp10u = 0x00,
@@ -48,6 +75,12 @@ pub enum RampTime {
     Ramp3400u = 0x07,
 }
 
+impl_try_from_u8!(
+    RampTime,
+    [Ramp10u, Ramp20u, Ramp40u, Ramp80u, Ramp200u, Ramp800u, Ramp1700u, Ramp3400u]
+);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct TxParams {
     power_dbm: i8,
     ramp_time: RampTime,
@@ -62,9 +95,27 @@ impl Default for TxParams {
     }
 }
 
-impl Into<[u8; 2]> for TxParams {
-    fn into(self) -> [u8; 2] {
-        [self.power_dbm as u8, self.ramp_time as u8]
+impl From<TxParams> for [u8; 2] {
+    fn from(params: TxParams) -> Self {
+        [params.power_dbm as u8, params.ramp_time as u8]
+    }
+}
+
+impl TryFrom<[u8; 2]> for TxParams {
+    type Error = InvalidValue;
+
+    fn try_from(raw: [u8; 2]) -> Result<Self, Self::Error> {
+        let power_dbm = raw[0] as i8;
+        if !(-17..=22).contains(&power_dbm) {
+            return Err(InvalidValue {
+                field: "power_dbm",
+                value: raw[0],
+            });
+        }
+        Ok(Self {
+            power_dbm,
+            ramp_time: raw[1].try_into()?,
+        })
     }
 }
 
@@ -86,24 +137,55 @@ impl TxParams {
         self.ramp_time = ramp_time;
         self
     }
+
+    pub fn power_dbm(&self) -> i8 {
+        self.power_dbm
+    }
 }
 
 #[repr(u8)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum DeviceSel {
     SX1262 = 0x00,
     SX1261 = 0x01,
 }
 
+impl_try_from_u8!(DeviceSel, [SX1262, SX1261]);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct PaConfig {
     pa_duty_cycle: u8,
     hp_max: u8,
     device_sel: DeviceSel,
 }
 
-impl Into<[u8; 4]> for PaConfig {
-    fn into(self) -> [u8; 4] {
-        [self.pa_duty_cycle, self.hp_max, self.device_sel as u8, 0x01]
+impl From<PaConfig> for [u8; 4] {
+    fn from(pa_config: PaConfig) -> Self {
+        [
+            pa_config.pa_duty_cycle,
+            pa_config.hp_max,
+            pa_config.device_sel as u8,
+            0x01,
+        ]
+    }
+}
+
+impl TryFrom<[u8; 4]> for PaConfig {
+    type Error = InvalidValue;
+
+    fn try_from(raw: [u8; 4]) -> Result<Self, Self::Error> {
+        // 13.1.14: paLut is reserved and always 0x01
+        if raw[3] != 0x01 {
+            return Err(InvalidValue {
+                field: "pa_lut",
+                value: raw[3],
+            });
+        }
+        Ok(Self {
+            pa_duty_cycle: raw[0],
+            hp_max: raw[1],
+            device_sel: raw[2].try_into()?,
+        })
     }
 }
 
@@ -132,6 +214,10 @@ impl PaConfig {
         self.device_sel = device_sel;
         self
     }
+
+    pub fn device_sel(&self) -> DeviceSel {
+        self.device_sel
+    }
 }
 
 pub struct RxBufferStatus {
@@ -157,3 +243,243 @@ impl RxBufferStatus {
         self.rx_start_buffer_pointer
     }
 }
+
+/// A packet received using SX126x::read_bytes
+#[derive(Copy, Clone, Debug)]
+pub struct RxPacket {
+    len: u8,
+    packet_status: PacketStatus,
+}
+
+impl RxPacket {
+    pub fn new(len: u8, packet_status: PacketStatus) -> Self {
+        Self { len, packet_status }
+    }
+
+    /// Number of bytes written to the receive buffer
+    pub fn len(&self) -> u8 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Status of the received packet, containing the RSSI and SNR
+    pub fn packet_status(&self) -> PacketStatus {
+        self.packet_status
+    }
+}
+
+/// Status of the last received packet, see 13.5.3.
+/// Its content depends on the packet type the modem was receiving with
+#[derive(Copy, Clone, Debug)]
+pub enum PacketStatus {
+    LoRa(LoRaPacketStatus),
+    Gfsk(GfskPacketStatus),
+}
+
+impl PacketStatus {
+    /// Decode the raw packet status returned by GetPacketStatus,
+    /// for a packet received using `packet_type`
+    pub fn new(packet_type: PacketType, raw: [u8; 3]) -> Self {
+        match packet_type {
+            PacketType::LoRa => Self::LoRa(raw.into()),
+            PacketType::GFSK => Self::Gfsk(raw.into()),
+        }
+    }
+
+    /// The LoRa packet status, None if the packet was received using GFSK
+    pub fn lora(self) -> Option<LoRaPacketStatus> {
+        match self {
+            Self::LoRa(status) => Some(status),
+            Self::Gfsk(_) => None,
+        }
+    }
+
+    /// The GFSK packet status, None if the packet was received using LoRa
+    pub fn gfsk(self) -> Option<GfskPacketStatus> {
+        match self {
+            Self::Gfsk(status) => Some(status),
+            Self::LoRa(_) => None,
+        }
+    }
+}
+
+/// Converts an RSSI as returned by the modem, which is -RSSI * 2, to dBm
+fn rssi_dbm(raw: u8) -> f32 {
+    -(raw as f32) / 2.
+}
+
+/// LoRa packet status. The RSSI values have a resolution of 0.5 dBm,
+/// the SNR has a resolution of 0.25 dB
+#[derive(Copy, Clone, Debug)]
+pub struct LoRaPacketStatus {
+    rssi_pkt: u8,
+    snr_pkt: i8,
+    signal_rssi_pkt: u8,
+}
+
+impl From<[u8; 3]> for LoRaPacketStatus {
+    fn from(raw: [u8; 3]) -> Self {
+        Self {
+            rssi_pkt: raw[0],
+            snr_pkt: raw[1] as i8,
+            signal_rssi_pkt: raw[2],
+        }
+    }
+}
+
+impl LoRaPacketStatus {
+    /// Average over last packet received of RSSI, in dBm
+    pub fn rssi_pkt(&self) -> f32 {
+        rssi_dbm(self.rssi_pkt)
+    }
+
+    /// Estimation of SNR on last packet received, in dB
+    pub fn snr_pkt(&self) -> f32 {
+        self.snr_pkt as f32 / 4.
+    }
+
+    /// Estimation of RSSI of the LoRa signal (after despreading)
+    /// on last packet received, in dBm
+    pub fn signal_rssi_pkt(&self) -> f32 {
+        rssi_dbm(self.signal_rssi_pkt)
+    }
+}
+
+/// GFSK packet status. The RSSI values have a resolution of 0.5 dBm
+#[derive(Copy, Clone, Debug)]
+pub struct GfskPacketStatus {
+    rx_status: u8,
+    rssi_sync: u8,
+    rssi_avg: u8,
+}
+
+impl From<[u8; 3]> for GfskPacketStatus {
+    fn from(raw: [u8; 3]) -> Self {
+        Self {
+            rx_status: raw[0],
+            rssi_sync: raw[1],
+            rssi_avg: raw[2],
+        }
+    }
+}
+
+impl GfskPacketStatus {
+    /// Raw RX status bits
+    pub fn rx_status(&self) -> u8 {
+        self.rx_status
+    }
+
+    pub fn preamble_err(&self) -> bool {
+        (self.rx_status & 1 << 7) > 0
+    }
+
+    pub fn sync_err(&self) -> bool {
+        (self.rx_status & 1 << 6) > 0
+    }
+
+    pub fn adrs_err(&self) -> bool {
+        (self.rx_status & 1 << 5) > 0
+    }
+
+    pub fn crc_err(&self) -> bool {
+        (self.rx_status & 1 << 4) > 0
+    }
+
+    pub fn length_err(&self) -> bool {
+        (self.rx_status & 1 << 3) > 0
+    }
+
+    pub fn abort_err(&self) -> bool {
+        (self.rx_status & 1 << 2) > 0
+    }
+
+    pub fn pkt_received(&self) -> bool {
+        (self.rx_status & 1 << 1) > 0
+    }
+
+    pub fn pkt_sent(&self) -> bool {
+        (self.rx_status & 1 << 0) > 0
+    }
+
+    /// RSSI in dBm, latched upon the detection of the sync address
+    pub fn rssi_sync(&self) -> f32 {
+        rssi_dbm(self.rssi_sync)
+    }
+
+    /// RSSI in dBm, averaged over the payload of the received packet
+    pub fn rssi_avg(&self) -> f32 {
+        rssi_dbm(self.rssi_avg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+
+    use super::*;
+
+    #[test]
+    fn tx_params_round_trip() {
+        for power_dbm in [-17, -9, 0, 14, 22] {
+            let params = TxParams::default()
+                .set_power_dbm(power_dbm)
+                .set_ramp_time(RampTime::Ramp800u);
+            assert_eq!(TxParams::try_from(<[u8; 2]>::from(params)), Ok(params));
+        }
+        assert!(TxParams::try_from([23, 0x00]).is_err());
+        assert!(TxParams::try_from([0, 0x08]).is_err());
+    }
+
+    #[test]
+    fn pa_config_round_trip() {
+        let params = PaConfig::default()
+            .set_pa_duty_cycle(0x04)
+            .set_hp_max(0x07)
+            .set_device_sel(DeviceSel::SX1261);
+        let raw = <[u8; 4]>::from(params);
+        assert_eq!(raw, [0x04, 0x07, 0x01, 0x01]);
+        assert_eq!(PaConfig::try_from(raw), Ok(params));
+        assert!(PaConfig::try_from([0x04, 0x07, 0x01, 0x00]).is_err());
+    }
+
+    #[test]
+    fn rx_tx_timeout_round_trip() {
+        for timeout in [
+            RxTxTimeout::from(0),
+            RxTxTimeout::from(0xFF_FFFF),
+            RxTxTimeout::from_ms(1000),
+            RxTxTimeout::from_us(15_625),
+        ] {
+            assert_eq!(RxTxTimeout::from(<[u8; 3]>::from(timeout)), timeout);
+        }
+        assert_eq!(
+            <[u8; 3]>::from(RxTxTimeout::from(0x12_3456)),
+            [0x12, 0x34, 0x56]
+        );
+        assert_eq!(u32::from(RxTxTimeout::from_ms(1000)), 64_000);
+        assert_eq!(u32::from(RxTxTimeout::from_us(15_625)), 1000);
+    }
+
+    #[test]
+    fn packet_status() {
+        // RSSI of -40 and -41.5 dBm, SNR of -2.5 dB
+        let status = PacketStatus::new(PacketType::LoRa, [0x50, 0xF6, 0x53]);
+        assert!(status.gfsk().is_none());
+        let status = status.lora().unwrap();
+        assert_eq!(status.rssi_pkt(), -40.);
+        assert_eq!(status.snr_pkt(), -2.5);
+        assert_eq!(status.signal_rssi_pkt(), -41.5);
+
+        // Packet received, RSSI of -40.5 and -41 dBm
+        let status = PacketStatus::new(PacketType::GFSK, [0x02, 0x51, 0x52]);
+        assert!(status.lora().is_none());
+        let status = status.gfsk().unwrap();
+        assert!(status.pkt_received());
+        assert!(!status.crc_err());
+        assert_eq!(status.rssi_sync(), -40.5);
+        assert_eq!(status.rssi_avg(), -41.);
+    }
+}
@@ -66,3 +66,39 @@ impl Status {
         }
     }
 }
+
+/// Statistics on the last few received packets, see 13.5.4
+#[derive(Copy, Clone, Debug)]
+pub struct RxStats {
+    pkt_received: u16,
+    crc_errors: u16,
+    header_errors: u16,
+}
+
+impl From<[u8; 6]> for RxStats {
+    fn from(raw: [u8; 6]) -> Self {
+        Self {
+            pkt_received: u16::from_be_bytes([raw[0], raw[1]]),
+            crc_errors: u16::from_be_bytes([raw[2], raw[3]]),
+            header_errors: u16::from_be_bytes([raw[4], raw[5]]),
+        }
+    }
+}
+
+impl RxStats {
+    /// Number of packets received
+    pub fn pkt_received(&self) -> u16 {
+        self.pkt_received
+    }
+
+    /// Number of packets received with a CRC error
+    pub fn crc_errors(&self) -> u16 {
+        self.crc_errors
+    }
+
+    /// Number of packets received with a header error in LoRa mode,
+    /// or with a length error in GFSK mode
+    pub fn header_errors(&self) -> u16 {
+        self.header_errors
+    }
+}
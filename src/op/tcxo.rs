@@ -1,5 +1,5 @@
 #[repr(u8)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TcxoVoltage {
     Volt1_6 = 0x00,
     Volt1_7 = 0x01,
@@ -11,14 +11,32 @@ pub enum TcxoVoltage {
     Volt3_3 = 0x07,
 }
 
-#[derive(Copy, Clone)]
+impl_try_from_u8!(
+    TcxoVoltage,
+    [Volt1_6, Volt1_7, Volt1_8, Volt2_2, Volt2_4, Volt2_7, Volt3_0, Volt3_3]
+);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct TcxoDelay {
     inner: [u8; 3],
 }
 
-impl Into<[u8; 3]> for TcxoDelay {
-    fn into(self) -> [u8; 3] {
-        self.inner
+impl From<TcxoDelay> for [u8; 3] {
+    fn from(delay: TcxoDelay) -> Self {
+        delay.inner
+    }
+}
+
+impl From<TcxoDelay> for u32 {
+    fn from(delay: TcxoDelay) -> Self {
+        let [b2, b1, b0] = delay.inner;
+        u32::from_be_bytes([0, b2, b1, b0])
+    }
+}
+
+impl From<[u8; 3]> for TcxoDelay {
+    fn from(inner: [u8; 3]) -> Self {
+        Self { inner }
     }
 }
 
@@ -30,3 +48,26 @@ impl TcxoDelay {
         Self { inner }
     }
 }
+
+/// Configuration of a TCXO powered through the dio3 pin, see 13.3.6
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TcxoConfig {
+    /// Supply voltage of the TCXO
+    pub voltage: TcxoVoltage,
+    /// Time the TCXO takes to start up
+    pub delay: TcxoDelay,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TcxoDelay;
+
+    #[test]
+    fn tcxo_delay_round_trip() {
+        let delay = TcxoDelay::from_ms(5);
+        let raw = <[u8; 3]>::from(delay);
+        assert_eq!(raw, [0x00, 0x01, 0x40]);
+        assert_eq!(TcxoDelay::from(raw), delay);
+        assert_eq!(u32::from(delay), 320);
+    }
+}
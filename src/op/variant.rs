@@ -0,0 +1,56 @@
+use core::ops::RangeInclusive;
+
+use super::modulation::lora::{LoRaBandWidth, LoRaSpreadFactor};
+use super::DeviceSel;
+
+/// Member of the SX126x family
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Variant {
+    SX1261,
+    SX1262,
+    SX1268,
+    /// Low cost variant of the SX1262, which supports a subset of its LoRa modulations
+    LLCC68,
+}
+
+impl Variant {
+    /// The power amplifier used by this variant
+    pub fn device_sel(self) -> DeviceSel {
+        match self {
+            Self::SX1261 => DeviceSel::SX1261,
+            Self::SX1262 | Self::SX1268 | Self::LLCC68 => DeviceSel::SX1262,
+        }
+    }
+
+    /// Supported RF frequencies in Hz
+    pub fn frequency_range(self) -> RangeInclusive<u32> {
+        match self {
+            Self::SX1261 | Self::SX1262 | Self::LLCC68 => 150_000_000..=960_000_000,
+            Self::SX1268 => 410_000_000..=810_000_000,
+        }
+    }
+
+    /// Power in dBm that can be passed to SetTxParams, see 13.4.4
+    pub fn tx_power_range(self) -> RangeInclusive<i8> {
+        match self.device_sel() {
+            DeviceSel::SX1261 => -17..=14,
+            DeviceSel::SX1262 => -9..=22,
+        }
+    }
+
+    /// Whether this variant supports the LoRa modulation with the given spreading factor
+    /// and bandwidth. The LLCC68 only supports bandwidths of 125, 250 and 500 kHz,
+    /// up to SF9, SF10 and SF11 respectively
+    pub fn supports_lora(self, spread_factor: LoRaSpreadFactor, bandwidth: LoRaBandWidth) -> bool {
+        if self != Self::LLCC68 {
+            return true;
+        }
+        let max_spread_factor = match bandwidth {
+            LoRaBandWidth::BW125 => LoRaSpreadFactor::SF9,
+            LoRaBandWidth::BW250 => LoRaSpreadFactor::SF10,
+            LoRaBandWidth::BW500 => LoRaSpreadFactor::SF11,
+            _ => return false,
+        };
+        spread_factor as u8 <= max_spread_factor as u8
+    }
+}
@@ -1,6 +1,13 @@
 //! Registers as defined in chapter 12
+use core::convert::TryFrom;
+
+mod values;
+
+pub use values::*;
+
 #[allow(dead_code)]
 #[repr(u16)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 /// Every register defined in the SX126X datasheet
 /// See table 12-1 in the datasheet
 pub enum Register {
@@ -96,3 +103,58 @@ pub enum Register {
     /// Used to clear events
     EventMask = 0x0944,
 }
+
+impl Register {
+    /// All registers, in order of address
+    const ALL: [Self; 36] = [
+        Self::DioxOutputEnable,
+        Self::DioxInputEnable,
+        Self::DioxPullUpControl,
+        Self::DioxPullDownControl,
+        Self::WhiteningInitialValueMsb,
+        Self::WhiteningInitialValueLsb,
+        Self::CrcMsbInitialValue,
+        Self::CrcLsbInitialValue,
+        Self::CrcMsbPolynomialValue,
+        Self::CrcLsbPolynomialValue,
+        Self::SyncWord0,
+        Self::SyncWord1,
+        Self::SyncWord2,
+        Self::SyncWord3,
+        Self::SyncWord4,
+        Self::SyncWord5,
+        Self::SyncWord6,
+        Self::SyncWord7,
+        Self::NodeAddress,
+        Self::BroadcastAddress,
+        Self::IqPolaritySetup,
+        Self::LoRaSyncWordMsb,
+        Self::LoRaSyncWordLsb,
+        Self::RandomNumberGen0,
+        Self::RandomNumberGen1,
+        Self::RandomNumberGen2,
+        Self::RandomNumberGen3,
+        Self::TxModulaton,
+        Self::RxGain,
+        Self::TxClampConfig,
+        Self::OcpConfiguration,
+        Self::RtcControl,
+        Self::XtaTrim,
+        Self::XtbTrim,
+        Self::Dio3OutputVoltageControl,
+        Self::EventMask,
+    ];
+}
+
+impl TryFrom<u16> for Register {
+    type Error = u16;
+
+    /// Look up the register at `addr`. Returns the address if it is not a known register
+    fn try_from(addr: u16) -> Result<Self, Self::Error> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|register| *register as u16 == addr)
+            .ok_or(addr)
+    }
+}
@@ -0,0 +1,360 @@
+//! Typed values of single byte registers, accessed using
+//! SX126x::read_reg, SX126x::write_reg and SX126x::modify_reg
+use super::Register;
+
+/// Typed value of a single byte register
+pub trait RegisterValue: Copy + From<u8> + Into<u8> {
+    /// Register holding the value
+    const REGISTER: Register;
+}
+
+/// Gain used in RX mode, see 9.6
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RxGain {
+    inner: u8,
+}
+
+impl RxGain {
+    /// Rx Power Saving gain, the default
+    pub const POWER_SAVING: Self = Self { inner: 0x94 };
+    /// Rx Boosted gain, improving the sensitivity at the cost of current consumption
+    pub const BOOSTED: Self = Self { inner: 0x96 };
+
+    pub fn is_boosted(&self) -> bool {
+        *self == Self::BOOSTED
+    }
+}
+
+impl From<u8> for RxGain {
+    fn from(inner: u8) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<RxGain> for u8 {
+    fn from(value: RxGain) -> Self {
+        value.inner
+    }
+}
+
+impl RegisterValue for RxGain {
+    const REGISTER: Register = Register::RxGain;
+}
+
+/// Over current protection level, in steps of 2.5 mA
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OcpConfiguration {
+    inner: u8,
+}
+
+impl OcpConfiguration {
+    /// Create a current limit from a value in mA, rounded down to steps of 2.5 mA
+    pub const fn from_ma(ma: u16) -> Self {
+        let ocp_trim = ma * 2 / 5;
+        let ocp_trim = if ocp_trim > 0x3F { 0x3F } else { ocp_trim };
+        Self {
+            inner: ocp_trim as u8,
+        }
+    }
+
+    /// Current limit in mA, rounded down
+    pub fn ma(&self) -> u16 {
+        (self.inner & 0x3F) as u16 * 5 / 2
+    }
+}
+
+impl From<u8> for OcpConfiguration {
+    fn from(inner: u8) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<OcpConfiguration> for u8 {
+    fn from(value: OcpConfiguration) -> Self {
+        value.inner
+    }
+}
+
+impl RegisterValue for OcpConfiguration {
+    const REGISTER: Register = Register::OcpConfiguration;
+}
+
+/// Implements a trimming capacitor value for the XTA or XTB pin
+macro_rules! xtal_trim {
+    ($name:ident) => {
+        /// Trimming capacitor value, from 0x00 to 0x2F. The capacitance is
+        /// 11.3 pF + 0.47 pF * value. Only change this while in STDBY_XOSC mode
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        pub struct $name {
+            inner: u8,
+        }
+
+        impl $name {
+            pub fn new(trim: u8) -> Self {
+                debug_assert!(trim <= 0x2F);
+                Self { inner: trim }
+            }
+
+            pub fn trim(&self) -> u8 {
+                self.inner
+            }
+        }
+
+        impl From<u8> for $name {
+            fn from(inner: u8) -> Self {
+                Self { inner }
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(value: $name) -> Self {
+                value.inner
+            }
+        }
+
+        impl RegisterValue for $name {
+            const REGISTER: Register = Register::$name;
+        }
+    };
+}
+
+xtal_trim!(XtaTrim);
+xtal_trim!(XtbTrim);
+
+/// IQ polarity setup. Bit 2 should be cleared when using inverted IQ, see 15.4
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct IqPolaritySetup {
+    inner: u8,
+}
+
+impl IqPolaritySetup {
+    const STANDARD_IQ: u8 = 1 << 2;
+
+    /// Whether the setup is optimized for inverted IQ
+    pub fn inverted_iq(&self) -> bool {
+        self.inner & Self::STANDARD_IQ == 0
+    }
+
+    pub fn set_inverted_iq(mut self, inverted_iq: bool) -> Self {
+        if inverted_iq {
+            self.inner &= !Self::STANDARD_IQ;
+        } else {
+            self.inner |= Self::STANDARD_IQ;
+        }
+        self
+    }
+}
+
+impl From<u8> for IqPolaritySetup {
+    fn from(inner: u8) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<IqPolaritySetup> for u8 {
+    fn from(value: IqPolaritySetup) -> Self {
+        value.inner
+    }
+}
+
+impl RegisterValue for IqPolaritySetup {
+    const REGISTER: Register = Register::IqPolaritySetup;
+}
+
+/// PA clamping configuration. Bits 4:1 hold the clamping threshold,
+/// which should be set to 0xF on the SX1262, see 15.2
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TxClampConfig {
+    inner: u8,
+}
+
+impl TxClampConfig {
+    const THRESHOLD_MASK: u8 = 0x1E;
+
+    pub fn threshold(&self) -> u8 {
+        (self.inner & Self::THRESHOLD_MASK) >> 1
+    }
+
+    /// Set the 4-bit clamping threshold
+    pub fn set_threshold(mut self, threshold: u8) -> Self {
+        debug_assert!(threshold <= 0x0F);
+        self.inner =
+            (self.inner & !Self::THRESHOLD_MASK) | ((threshold << 1) & Self::THRESHOLD_MASK);
+        self
+    }
+}
+
+impl From<u8> for TxClampConfig {
+    fn from(inner: u8) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<TxClampConfig> for u8 {
+    fn from(value: TxClampConfig) -> Self {
+        value.inner
+    }
+}
+
+impl RegisterValue for TxClampConfig {
+    const REGISTER: Register = Register::TxClampConfig;
+}
+
+/// Implements a non-standard DIOx control, in which bit n controls DIOn
+macro_rules! diox_control {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        pub struct $name {
+            inner: u8,
+        }
+
+        impl $name {
+            /// Whether the setting is enabled for DIO `dio`, from 1 to 3
+            pub fn dio(&self, dio: u8) -> bool {
+                debug_assert!((1..=3).contains(&dio));
+                self.inner & (1 << dio) != 0
+            }
+
+            /// Enable or disable the setting for DIO `dio`, from 1 to 3
+            pub fn set_dio(mut self, dio: u8, enabled: bool) -> Self {
+                debug_assert!((1..=3).contains(&dio));
+                if enabled {
+                    self.inner |= 1 << dio;
+                } else {
+                    self.inner &= !(1 << dio);
+                }
+                self
+            }
+        }
+
+        impl From<u8> for $name {
+            fn from(inner: u8) -> Self {
+                Self { inner }
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(value: $name) -> Self {
+                value.inner
+            }
+        }
+
+        impl RegisterValue for $name {
+            const REGISTER: Register = Register::$name;
+        }
+    };
+}
+
+diox_control!(
+    /// Output enable of the DIOx pins
+    DioxOutputEnable
+);
+diox_control!(
+    /// Input enable of the DIOx pins
+    DioxInputEnable
+);
+diox_control!(
+    /// Pull-up of the DIOx pins
+    DioxPullUpControl
+);
+diox_control!(
+    /// Pull-down of the DIOx pins
+    DioxPullDownControl
+);
+
+/// TX modulation setup. Bit 2 should be cleared when using LoRa with
+/// a bandwidth of 500 kHz, and set otherwise, see 15.1
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TxModulation {
+    inner: u8,
+}
+
+impl TxModulation {
+    const NOT_BW500: u8 = 1 << 2;
+
+    /// Whether the setup is optimized for LoRa with a bandwidth of 500 kHz
+    pub fn lora_bw500(&self) -> bool {
+        self.inner & Self::NOT_BW500 == 0
+    }
+
+    pub fn set_lora_bw500(mut self, lora_bw500: bool) -> Self {
+        if lora_bw500 {
+            self.inner &= !Self::NOT_BW500;
+        } else {
+            self.inner |= Self::NOT_BW500;
+        }
+        self
+    }
+}
+
+impl From<u8> for TxModulation {
+    fn from(inner: u8) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<TxModulation> for u8 {
+    fn from(value: TxModulation) -> Self {
+        value.inner
+    }
+}
+
+impl RegisterValue for TxModulation {
+    const REGISTER: Register = Register::TxModulaton;
+}
+
+/// RTC control. Writing zero stops the RTC, see 15.3
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RtcControl {
+    inner: u8,
+}
+
+impl RtcControl {
+    pub const STOPPED: Self = Self { inner: 0x00 };
+}
+
+impl From<u8> for RtcControl {
+    fn from(inner: u8) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<RtcControl> for u8 {
+    fn from(value: RtcControl) -> Self {
+        value.inner
+    }
+}
+
+impl RegisterValue for RtcControl {
+    const REGISTER: Register = Register::RtcControl;
+}
+
+/// Event mask. Setting bit 1 clears a pending timeout event, see 15.3
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EventMask {
+    inner: u8,
+}
+
+impl EventMask {
+    pub fn clear_timeout_event(mut self) -> Self {
+        self.inner |= 1 << 1;
+        self
+    }
+}
+
+impl From<u8> for EventMask {
+    fn from(inner: u8) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<EventMask> for u8 {
+    fn from(value: EventMask) -> Self {
+        value.inner
+    }
+}
+
+impl RegisterValue for EventMask {
+    const REGISTER: Register = Register::EventMask;
+}
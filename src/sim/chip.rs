@@ -0,0 +1,724 @@
+//! Model of the SX126x command interface, as seen from the SPI bus and the pins
+use std::vec;
+use std::vec::Vec;
+
+use crate::op::IrqMaskBit;
+use crate::reg::Register;
+
+use super::phy;
+use super::Mode;
+
+/// Time in ns the busy pin stays high after a regular command
+const COMMAND_NS: u64 = 2_000;
+/// Time in ns the busy pin stays high during calibration, see 13.1.12
+const CALIBRATION_NS: u64 = 3_500_000;
+/// Time in ns the busy pin stays high after a reset or a cold start
+const COLD_START_NS: u64 = 3_500_000;
+/// Time in ns the busy pin stays high after a warm start
+const WARM_START_NS: u64 = 340_000;
+/// 13.1.4: The RX/TX timeout is expressed in steps of 15.625 μs
+const TIMEOUT_STEP_NS: u64 = 15_625;
+/// 13.1.4: A timeout of 0xFFFFFF puts the device in continuous RX mode
+const RX_CONTINUOUS: u32 = 0xFFFFFF;
+/// Size of the register address space
+const REGISTER_SPACE: usize = 0x1000;
+
+const NOP: u8 = 0x00;
+const PACKET_TYPE_LORA: u8 = 0x01;
+
+/// Command status as reported in the status byte, see table 13-76
+const CMD_DATA_AVAILABLE: u8 = 0x02;
+const CMD_TIMEOUT: u8 = 0x03;
+const CMD_PROCESSING_ERROR: u8 = 0x04;
+const CMD_TX_DONE: u8 = 0x06;
+
+/// Operation that completes at a later point in time
+#[derive(Copy, Clone)]
+pub(crate) enum Pending {
+    TxDone,
+    TxTimeout,
+    RxTimeout,
+    CadDone,
+}
+
+/// Radio settings that have to match for a receiver to pick up a transmission
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct Channel {
+    rf_freq: u32,
+    packet_type: u8,
+    /// Spreading factor and bandwidth in LoRa mode, bit rate in GFSK mode
+    modulation: [u8; 3],
+    invert_iq: bool,
+    sync_word: Vec<u8>,
+}
+
+impl Channel {
+    /// Whether signals on both channels can be demodulated by the same receiver,
+    /// regardless of the sync word and IQ setup
+    pub(crate) fn interferes(&self, other: &Channel) -> bool {
+        self.rf_freq == other.rf_freq
+            && self.packet_type == other.packet_type
+            && self.modulation == other.modulation
+    }
+}
+
+/// Packet sent by a chip
+pub(crate) struct Transmission {
+    pub(crate) channel: Channel,
+    pub(crate) power_dbm: i8,
+    pub(crate) start: u64,
+    /// Latest time a receiver can start listening and still detect the preamble
+    pub(crate) detect: u64,
+    pub(crate) end: u64,
+    pub(crate) payload: Vec<u8>,
+    pub(crate) crc: bool,
+}
+
+/// Part of the state that is lost on a reset or a cold start
+struct State {
+    mode: Mode,
+    /// Time at which RX or CAD mode was entered
+    mode_since: u64,
+    busy_until: u64,
+    deadline: Option<(u64, Pending)>,
+    rx_continuous: bool,
+    /// Whether the RX timeout was stopped by a detected packet
+    rx_held: bool,
+    /// Whether PreambleDetected was raised for the packet being received
+    preamble_detected: bool,
+    command_status: u8,
+    packet_type: u8,
+    rf_freq: u32,
+    mod_params: [u8; 8],
+    packet_params: [u8; 9],
+    tx_params: [u8; 2],
+    pa_config: [u8; 4],
+    cad_params: [u8; 7],
+    tx_base_addr: u8,
+    rx_base_addr: u8,
+    buffer: [u8; 256],
+    registers: Vec<u8>,
+    irq_mask: u16,
+    dio1_mask: u16,
+    irq_status: u16,
+    rx_buffer_status: [u8; 2],
+    packet_status: [u8; 3],
+    rssi_inst: u8,
+    stats: [u16; 3],
+}
+
+impl State {
+    fn power_on(busy_until: u64) -> Self {
+        let mut registers = vec![0; REGISTER_SPACE];
+        // Reset values, see table 12-1
+        let defaults: [(u16, &[u8]); 9] = [
+            (Register::WhiteningInitialValueMsb as u16, &[0x01, 0x00]),
+            (Register::CrcMsbInitialValue as u16, &[0x1D, 0x0F]),
+            (Register::CrcMsbPolynomialValue as u16, &[0x10, 0x21]),
+            (
+                Register::SyncWord0 as u16,
+                &[0x97, 0x23, 0x52, 0x25, 0x56, 0x53, 0x65, 0x64],
+            ),
+            (Register::LoRaSyncWordMsb as u16, &[0x14, 0x24]),
+            (Register::IqPolaritySetup as u16, &[0x0D]),
+            (Register::RxGain as u16, &[0x94]),
+            (Register::OcpConfiguration as u16, &[0x38]),
+            (Register::XtaTrim as u16, &[0x05, 0x05]),
+        ];
+        for (addr, value) in defaults.iter() {
+            let addr = *addr as usize;
+            registers[addr..addr + value.len()].copy_from_slice(value);
+        }
+        Self {
+            mode: Mode::StbyRc,
+            mode_since: 0,
+            busy_until,
+            deadline: None,
+            rx_continuous: false,
+            rx_held: false,
+            preamble_detected: false,
+            command_status: 0,
+            packet_type: 0,
+            rf_freq: 0,
+            mod_params: [0; 8],
+            packet_params: [0; 9],
+            tx_params: [0; 2],
+            pa_config: [0; 4],
+            cad_params: [0; 7],
+            tx_base_addr: 0,
+            rx_base_addr: 0,
+            buffer: [0; 256],
+            registers,
+            irq_mask: 0,
+            dio1_mask: 0,
+            irq_status: 0,
+            rx_buffer_status: [0; 2],
+            packet_status: [0; 3],
+            rssi_inst: 0,
+            stats: [0; 3],
+        }
+    }
+}
+
+/// Software model of a single SX126x
+pub(crate) struct Chip {
+    state: State,
+    warm_start: bool,
+    nrst_low: bool,
+    ant_enabled: bool,
+    channel_active: bool,
+    /// Bytes clocked in since nss went low
+    frame: Vec<u8>,
+    /// Whether the current frame is dropped because the chip was not ready
+    frame_ignored: bool,
+    /// Payloads transmitted since they were last taken
+    transmitted: Vec<Vec<u8>>,
+    /// Transmission started by the last command, to be picked up by the medium
+    started: Option<Transmission>,
+}
+
+impl Chip {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: State::power_on(0),
+            warm_start: false,
+            nrst_low: false,
+            ant_enabled: false,
+            channel_active: false,
+            frame: Vec::new(),
+            frame_ignored: false,
+            transmitted: Vec::new(),
+            started: None,
+        }
+    }
+
+    /// Operation in progress and the time at which it completes
+    pub(crate) fn deadline(&self) -> Option<(u64, Pending)> {
+        self.state.deadline
+    }
+
+    /// Complete the operation in progress. `cad_detected` tells
+    /// whether channel activity detection found any activity
+    pub(crate) fn complete(&mut self, at: u64, cad_detected: bool) {
+        let pending = match self.state.deadline.take() {
+            Some((_, pending)) => pending,
+            None => return,
+        };
+        match pending {
+            Pending::TxDone => {
+                self.raise(IrqMaskBit::TxDone as u16);
+                self.state.command_status = CMD_TX_DONE;
+                self.state.mode = Mode::StbyRc;
+            }
+            Pending::TxTimeout | Pending::RxTimeout => {
+                self.raise(IrqMaskBit::Timeout as u16);
+                self.state.command_status = CMD_TIMEOUT;
+                self.state.mode = Mode::StbyRc;
+            }
+            Pending::CadDone => {
+                let detected = cad_detected || self.channel_active;
+                let mut irq = IrqMaskBit::CadDone as u16;
+                if detected {
+                    irq |= IrqMaskBit::CadDetected as u16;
+                }
+                self.raise(irq);
+                // CadRx exit mode: stay in RX if activity was detected
+                if detected && self.state.cad_params[3] == 0x01 {
+                    let timeout = u32::from_be_bytes([
+                        0,
+                        self.state.cad_params[4],
+                        self.state.cad_params[5],
+                        self.state.cad_params[6],
+                    ]);
+                    self.start_rx(at, timeout);
+                } else {
+                    self.state.mode = Mode::StbyRc;
+                }
+            }
+        }
+    }
+
+    /// 13.1.4: The RX timeout is stopped when a packet is detected
+    pub(crate) fn hold_rx(&mut self) {
+        self.state.rx_held = self.state.deadline.take().is_some();
+    }
+
+    /// Restart the RX timeout stopped by `hold_rx` when the detected packet ended at `at`
+    /// without being received, so that it times out unless another packet is detected
+    pub(crate) fn release_rx(&mut self, at: u64) {
+        if self.state.rx_held && self.state.mode == Mode::Rx {
+            self.state.deadline = Some((at, Pending::RxTimeout));
+        }
+        self.state.rx_held = false;
+    }
+
+    /// Raise PreambleDetected for the packet being received, once
+    pub(crate) fn detect_preamble(&mut self) {
+        if self.state.mode == Mode::Rx && !self.state.preamble_detected {
+            self.state.preamble_detected = true;
+            self.raise(IrqMaskBit::PreambleDetected as u16);
+        }
+    }
+
+    pub(crate) fn busy(&self, now: u64) -> bool {
+        self.nrst_low || self.state.mode == Mode::Sleep || now < self.state.busy_until
+    }
+
+    pub(crate) fn dio1(&self) -> bool {
+        self.state.irq_status & self.state.dio1_mask != 0
+    }
+
+    pub(crate) fn set_nrst(&mut self, now: u64, high: bool) {
+        if high && self.nrst_low {
+            // Rising edge on nrst: the chip restarts
+            self.state = State::power_on(now + COLD_START_NS);
+        }
+        self.nrst_low = !high;
+    }
+
+    pub(crate) fn set_ant(&mut self, high: bool) {
+        self.ant_enabled = high;
+    }
+
+    /// Falling edge on nss. Wakes the chip up if it is asleep, in which case the
+    /// frame is ignored. Frames sent while the chip is busy are ignored as well
+    pub(crate) fn select(&mut self, now: u64) {
+        self.frame.clear();
+        self.frame_ignored = self.busy(now);
+        if self.state.mode == Mode::Sleep && !self.nrst_low {
+            if self.warm_start {
+                self.state.mode = Mode::StbyRc;
+                self.state.busy_until = now + WARM_START_NS;
+            } else {
+                self.state = State::power_on(now + COLD_START_NS);
+            }
+        }
+    }
+
+    /// Clock in a byte on mosi and return the byte clocked out on miso
+    pub(crate) fn transfer(&mut self, mosi: u8) -> u8 {
+        self.frame.push(mosi);
+        if self.frame_ignored {
+            return NOP;
+        }
+        let index = self.frame.len() - 1;
+        let state = &self.state;
+        let status = self.status();
+        let response = |offset: usize, data: &[u8]| {
+            index
+                .checked_sub(offset)
+                .and_then(|i| data.get(i).copied())
+                .unwrap_or(status)
+        };
+        match self.frame[0] {
+            // GetIrqStatus
+            0x12 => response(2, &state.irq_status.to_be_bytes()),
+            // GetRxBufferStatus
+            0x13 => response(2, &state.rx_buffer_status),
+            // GetPacketStatus
+            0x14 => response(2, &state.packet_status),
+            // GetRssiInst
+            0x15 => response(2, &[state.rssi_inst]),
+            // GetStats
+            0x10 => {
+                let mut stats = [0; 6];
+                for (chunk, stat) in stats.chunks_mut(2).zip(state.stats.iter()) {
+                    chunk.copy_from_slice(&stat.to_be_bytes());
+                }
+                response(2, &stats)
+            }
+            // GetPacketType
+            0x11 => response(2, &[state.packet_type]),
+            // GetDeviceErrors
+            0x17 => response(2, &[0, 0]),
+            // ReadRegister
+            0x1D if index >= 4 => {
+                let addr = u16::from_be_bytes([self.frame[1], self.frame[2]]) as usize;
+                state
+                    .registers
+                    .get(addr + index - 4)
+                    .copied()
+                    .unwrap_or(NOP)
+            }
+            // ReadBuffer
+            0x1E if index >= 3 => {
+                let offset = self.frame[1].wrapping_add((index - 3) as u8);
+                state.buffer[offset as usize]
+            }
+            _ => status,
+        }
+    }
+
+    /// Rising edge on nss, executes the command that was clocked in
+    pub(crate) fn deselect(&mut self, now: u64) {
+        if self.frame_ignored || self.frame.is_empty() {
+            return;
+        }
+        let frame = core::mem::take(&mut self.frame);
+        self.state.command_status = 0;
+        self.state.busy_until = now + COMMAND_NS;
+        if self.execute(now, &frame).is_none() {
+            self.state.command_status = CMD_PROCESSING_ERROR;
+        }
+    }
+
+    /// Execute a command. Returns None if the opcode is unknown or
+    /// not enough arguments were passed
+    fn execute(&mut self, now: u64, frame: &[u8]) -> Option<()> {
+        let args = &frame[1..];
+        let arg = |i: usize| args.get(i).copied();
+        let state = &mut self.state;
+        match frame[0] {
+            // SetSleep
+            0x84 => {
+                self.warm_start = arg(0)? & 0x04 != 0;
+                state.mode = Mode::Sleep;
+                state.deadline = None;
+            }
+            // SetStandby
+            0x80 => {
+                state.mode = if arg(0)? == 0 {
+                    Mode::StbyRc
+                } else {
+                    Mode::StbyXosc
+                };
+                state.deadline = None;
+            }
+            // SetFs
+            0xC1 => state.mode = Mode::Fs,
+            // SetTx
+            0x83 => {
+                let timeout = u32::from_be_bytes([0, arg(0)?, arg(1)?, arg(2)?]);
+                self.start_tx(timeout);
+            }
+            // SetRx
+            0x82 => {
+                let timeout = u32::from_be_bytes([0, arg(0)?, arg(1)?, arg(2)?]);
+                self.start_rx(now, timeout);
+            }
+            // SetRxDutyCycle: modelled as RX without timeout
+            0x94 => {
+                arg(5)?;
+                self.start_rx(now, 0);
+            }
+            // SetCad
+            0xC5 => {
+                state.mode = Mode::Cad;
+                state.mode_since = now;
+                let duration = self.cad_duration_ns();
+                self.state.deadline = Some((now + duration, Pending::CadDone));
+            }
+            // SetTxContinuousWave, SetTxInfinitePreamble
+            0xD1 | 0xD2 => {
+                state.mode = Mode::Tx;
+                state.deadline = None;
+            }
+            // Calibrate, CalibrateImage
+            0x89 | 0x98 => state.busy_until = now + CALIBRATION_NS,
+            // SetRegulatorMode, SetDIO2AsRfSwitchCtrl, StopTimerOnPreamble, SetLoRaSymbNumTimeout
+            0x96 | 0x9D | 0x9F | 0xA0 => {
+                arg(0)?;
+            }
+            // SetDIO3AsTCXOCtrl
+            0x97 => {
+                arg(3)?;
+            }
+            // SetPaConfig
+            0x95 => {
+                state.pa_config.copy_from_slice(args.get(..4)?);
+                // 13.1.14: The over current protection is reset to 60 mA
+                // for the SX1261 PA, and to 140 mA for the SX1262 PA
+                let ocp = if state.pa_config[2] == 0x01 {
+                    0x18
+                } else {
+                    0x38
+                };
+                state.registers[Register::OcpConfiguration as usize] = ocp;
+            }
+            // WriteRegister
+            0x0D => {
+                let addr = u16::from_be_bytes([arg(0)?, arg(1)?]) as usize;
+                let data = &args[2..];
+                let end = (addr + data.len()).min(REGISTER_SPACE);
+                if addr < end {
+                    state.registers[addr..end].copy_from_slice(&data[..end - addr]);
+                }
+            }
+            // WriteBuffer
+            0x0E => {
+                let offset = arg(0)?;
+                for (i, b) in args[1..].iter().enumerate() {
+                    state.buffer[offset.wrapping_add(i as u8) as usize] = *b;
+                }
+            }
+            // SetDioIrqParams
+            0x08 => {
+                arg(7)?;
+                state.irq_mask = u16::from_be_bytes([args[0], args[1]]);
+                state.dio1_mask = u16::from_be_bytes([args[2], args[3]]);
+            }
+            // ClearIrqStatus
+            0x02 => state.irq_status &= !u16::from_be_bytes([arg(0)?, arg(1)?]),
+            // SetRfFrequency
+            0x86 => state.rf_freq = u32::from_be_bytes([arg(0)?, arg(1)?, arg(2)?, arg(3)?]),
+            // SetPacketType
+            0x8A => state.packet_type = arg(0)?,
+            // SetTxParams
+            0x8E => state.tx_params.copy_from_slice(args.get(..2)?),
+            // SetModulationParams
+            0x8B => {
+                let len = args.len().min(8);
+                state.mod_params[..len].copy_from_slice(&args[..len]);
+            }
+            // SetPacketParams
+            0x8C => {
+                let len = args.len().min(9);
+                state.packet_params[..len].copy_from_slice(&args[..len]);
+            }
+            // SetCadParams
+            0x88 => state.cad_params.copy_from_slice(args.get(..7)?),
+            // SetBufferBaseAddress
+            0x8F => {
+                state.tx_base_addr = arg(0)?;
+                state.rx_base_addr = arg(1)?;
+            }
+            // ResetStats
+            0x00 => state.stats = [0; 3],
+            // ClearDeviceErrors
+            0x07 => {}
+            // Read commands, the response was given while clocking
+            0xC0 | 0x10 | 0x11 | 0x12 | 0x13 | 0x14 | 0x15 | 0x17 | 0x1D | 0x1E => {}
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Put the chip in TX mode and start sending the payload once the command is
+    /// processed. The timeout is in steps of 15.625 μs, 0 disables it
+    fn start_tx(&mut self, timeout: u32) {
+        let state = &self.state;
+        let len = self.payload_len();
+        let payload: Vec<u8> = (0..len)
+            .map(|i| state.buffer[state.tx_base_addr.wrapping_add(i) as usize])
+            .collect();
+        let start = state.busy_until;
+        let time_on_air =
+            phy::time_on_air_ns(state.packet_type, &state.mod_params, &state.packet_params);
+        let detect = start
+            + phy::preamble_detect_ns(state.packet_type, &state.mod_params, &state.packet_params);
+        let (end, pending) = match timeout as u64 * TIMEOUT_STEP_NS {
+            timeout if timeout > 0 && timeout < time_on_air => {
+                (start + timeout, Pending::TxTimeout)
+            }
+            _ => (start + time_on_air, Pending::TxDone),
+        };
+        let crc = if state.packet_type == PACKET_TYPE_LORA {
+            state.packet_params[4] == 0x01
+        } else {
+            state.packet_params[7] != 0x01
+        };
+        self.started = Some(Transmission {
+            channel: self.channel(),
+            power_dbm: state.tx_params[0] as i8,
+            start,
+            detect,
+            end,
+            payload: payload.clone(),
+            crc,
+        });
+        self.transmitted.push(payload);
+        self.state.mode = Mode::Tx;
+        self.state.deadline = Some((end, pending));
+    }
+
+    /// Put the chip in RX mode with a timeout in steps of 15.625 μs
+    fn start_rx(&mut self, now: u64, timeout: u32) {
+        self.state.mode = Mode::Rx;
+        self.state.mode_since = now;
+        self.state.rx_continuous = timeout == RX_CONTINUOUS;
+        self.state.rx_held = false;
+        self.state.preamble_detected = false;
+        self.state.deadline = match timeout {
+            0 | RX_CONTINUOUS => None,
+            timeout => Some((now + timeout as u64 * TIMEOUT_STEP_NS, Pending::RxTimeout)),
+        };
+    }
+
+    /// Receive a packet if the chip is in RX mode. Returns whether it was received
+    pub(crate) fn receive(&mut self, payload: &[u8], rssi: i16, snr: i8, crc_ok: bool) -> bool {
+        if self.state.mode != Mode::Rx {
+            return false;
+        }
+        let state = &mut self.state;
+        let len = payload.len().min(u8::MAX as usize);
+        let base = state.rx_base_addr;
+        for (i, b) in payload[..len].iter().enumerate() {
+            state.buffer[base.wrapping_add(i as u8) as usize] = *b;
+        }
+        state.rx_buffer_status = [len as u8, base];
+        state.rx_held = false;
+        state.preamble_detected = false;
+        let rssi = rssi
+            .saturating_neg()
+            .saturating_mul(2)
+            .clamp(0, u8::MAX as i16) as u8;
+        let mut irq = IrqMaskBit::PreambleDetected as u16 | IrqMaskBit::RxDone as u16;
+        if state.packet_type == PACKET_TYPE_LORA {
+            state.packet_status = [rssi, (snr as i16 * 4).clamp(-128, 127) as u8, rssi];
+            irq |= IrqMaskBit::HeaderValid as u16;
+        } else {
+            state.packet_status = [0, rssi, rssi];
+            irq |= IrqMaskBit::SyncwordValid as u16;
+        }
+        state.stats[0] = state.stats[0].wrapping_add(1);
+        if !crc_ok {
+            irq |= IrqMaskBit::CrcErr as u16;
+            state.stats[1] = state.stats[1].wrapping_add(1);
+        }
+        state.command_status = CMD_DATA_AVAILABLE;
+        if !state.rx_continuous {
+            state.mode = Mode::StbyRc;
+            state.deadline = None;
+        }
+        self.raise(irq);
+        true
+    }
+
+    /// Set IRQ flags, as far as they are enabled
+    fn raise(&mut self, irq: u16) {
+        self.state.irq_status |= irq & self.state.irq_mask;
+    }
+
+    /// 13.5.1: Status byte, containing the chip mode and command status
+    fn status(&self) -> u8 {
+        let mode = match self.state.mode {
+            Mode::Sleep => 0x00,
+            Mode::StbyRc => 0x02,
+            Mode::StbyXosc => 0x03,
+            Mode::Fs => 0x04,
+            Mode::Rx | Mode::Cad => 0x05,
+            Mode::Tx => 0x06,
+        };
+        mode << 4 | self.state.command_status << 1
+    }
+
+    /// Payload length from the packet params
+    fn payload_len(&self) -> u8 {
+        if self.state.packet_type == PACKET_TYPE_LORA {
+            self.state.packet_params[3]
+        } else {
+            self.state.packet_params[6]
+        }
+    }
+
+    /// Duration of channel activity detection, based on the number of
+    /// symbols in the CAD params and the LoRa modulation params
+    fn cad_duration_ns(&self) -> u64 {
+        let symbols = 1 << self.state.cad_params[0].min(4);
+        symbols * phy::lora_symbol_time_ns(&self.state.mod_params)
+    }
+
+    /// Radio settings used to send and receive packets
+    pub(crate) fn channel(&self) -> Channel {
+        let state = &self.state;
+        let (modulation, invert_iq, sync_word) = if state.packet_type == PACKET_TYPE_LORA {
+            let addr = Register::LoRaSyncWordMsb as usize;
+            (
+                [state.mod_params[0], state.mod_params[1], 0],
+                state.packet_params[5] == 0x01,
+                state.registers[addr..addr + 2].to_vec(),
+            )
+        } else {
+            let addr = Register::SyncWord0 as usize;
+            let len = (state.packet_params[3] as usize).div_ceil(8).min(8);
+            (
+                [
+                    state.mod_params[0],
+                    state.mod_params[1],
+                    state.mod_params[2],
+                ],
+                false,
+                state.registers[addr..addr + len].to_vec(),
+            )
+        };
+        Channel {
+            rf_freq: state.rf_freq,
+            packet_type: state.packet_type,
+            modulation,
+            invert_iq,
+            sync_word,
+        }
+    }
+
+    /// Time at which RX or CAD mode was entered
+    pub(crate) fn mode_since(&self) -> u64 {
+        self.state.mode_since
+    }
+
+    /// Set the RSSI in dBm reported by GetRssiInst
+    pub(crate) fn set_rssi_inst(&mut self, rssi: f32) {
+        self.state.rssi_inst = (-rssi * 2.).clamp(0., u8::MAX as f32) as u8;
+    }
+
+    /// Noise floor in dBm within the receiver bandwidth
+    pub(crate) fn noise_floor_dbm(&self, noise_figure: f32) -> f32 {
+        phy::noise_floor_dbm(self.state.packet_type, &self.state.mod_params, noise_figure)
+    }
+
+    /// Minimum SNR in dB needed to demodulate a packet
+    pub(crate) fn demodulation_snr_db(&self) -> f32 {
+        phy::demodulation_snr_db(self.state.packet_type, &self.state.mod_params)
+    }
+
+    pub(crate) fn take_transmission(&mut self) -> Option<Transmission> {
+        self.started.take()
+    }
+
+    pub(crate) fn mode(&self) -> Mode {
+        self.state.mode
+    }
+
+    pub(crate) fn irq_status(&self) -> u16 {
+        self.state.irq_status
+    }
+
+    pub(crate) fn register(&self, addr: u16) -> u8 {
+        self.state
+            .registers
+            .get(addr as usize)
+            .copied()
+            .unwrap_or(NOP)
+    }
+
+    pub(crate) fn buffer(&self) -> [u8; 256] {
+        self.state.buffer
+    }
+
+    pub(crate) fn rf_freq(&self) -> u32 {
+        self.state.rf_freq
+    }
+
+    pub(crate) fn packet_type(&self) -> u8 {
+        self.state.packet_type
+    }
+
+    pub(crate) fn mod_params(&self) -> [u8; 8] {
+        self.state.mod_params
+    }
+
+    pub(crate) fn packet_params(&self) -> [u8; 9] {
+        self.state.packet_params
+    }
+
+    pub(crate) fn ant_enabled(&self) -> bool {
+        self.ant_enabled
+    }
+
+    pub(crate) fn set_channel_active(&mut self, active: bool) {
+        self.channel_active = active;
+    }
+
+    pub(crate) fn take_transmitted(&mut self) -> Vec<Vec<u8>> {
+        core::mem::take(&mut self.transmitted)
+    }
+}
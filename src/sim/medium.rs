@@ -0,0 +1,577 @@
+//! Virtual air connecting simulated devices
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::vec::Vec;
+
+use super::chip::{Chip, Pending, Transmission};
+use super::{Clock, Device, Mode};
+
+/// Path loss in dB between devices without a configured path loss
+const DEFAULT_PATH_LOSS_DB: f32 = 80.;
+/// Noise figure of the simulated receivers in dB
+const DEFAULT_NOISE_FIGURE_DB: f32 = 6.;
+/// A packet survives a collision if it is this much stronger than the interferer
+const CAPTURE_THRESHOLD_DB: f32 = 6.;
+/// Transmissions that ended longer than this ago in ns can no longer collide
+const RETENTION_NS: u64 = 60_000_000_000;
+
+/// Devices sharing the air, together with the packets they sent
+pub(crate) struct Air {
+    pub(crate) chips: Vec<Chip>,
+    transmissions: Vec<(usize, Transmission)>,
+    default_path_loss: f32,
+    path_loss: HashMap<(usize, usize), f32>,
+    noise_figure: f32,
+}
+
+impl Air {
+    fn new() -> Self {
+        Self {
+            chips: Vec::new(),
+            transmissions: Vec::new(),
+            default_path_loss: DEFAULT_PATH_LOSS_DB,
+            path_loss: HashMap::new(),
+            noise_figure: DEFAULT_NOISE_FIGURE_DB,
+        }
+    }
+
+    /// Complete all operations that are due, in chronological order
+    pub(crate) fn update(&mut self, now: u64) {
+        loop {
+            let next = self
+                .chips
+                .iter()
+                .enumerate()
+                .filter_map(|(id, chip)| chip.deadline().map(|(at, pending)| (at, id, pending)))
+                .filter(|(at, _, _)| *at <= now)
+                .min_by_key(|(at, _, _)| *at);
+            let (at, id, pending) = match next {
+                Some(next) => next,
+                None => break,
+            };
+            match pending {
+                Pending::TxDone => {
+                    self.chips[id].complete(at, false);
+                    self.deliver(id, at);
+                    // Receivers still waiting on a packet that was lost time out,
+                    // unless they detected another packet, which holds them again
+                    for chip in self.chips.iter_mut() {
+                        chip.release_rx(at);
+                    }
+                }
+                Pending::RxTimeout if self.detected(id, at) => self.chips[id].hold_rx(),
+                Pending::CadDone => {
+                    let detected = self.activity(id, at);
+                    self.chips[id].complete(at, detected);
+                }
+                Pending::TxTimeout | Pending::RxTimeout => self.chips[id].complete(at, false),
+            }
+        }
+        self.transmissions
+            .retain(|(_, transmission)| transmission.end + RETENTION_NS >= now);
+        for id in 0..self.chips.len() {
+            if self.chips[id].mode() == Mode::Rx {
+                let rssi = self.rssi_inst(id, now);
+                self.chips[id].set_rssi_inst(rssi);
+                if self.detected(id, now) {
+                    self.chips[id].detect_preamble();
+                }
+            }
+        }
+    }
+
+    /// Pick up the transmission started by the last command of a chip
+    pub(crate) fn collect(&mut self, id: usize) {
+        if let Some(transmission) = self.chips[id].take_transmission() {
+            self.transmissions.push((id, transmission));
+        }
+    }
+
+    /// Received power in dBm at chip `to`
+    fn received_power(&self, from: usize, transmission: &Transmission, to: usize) -> f32 {
+        transmission.power_dbm as f32 - self.path_loss(from, to)
+    }
+
+    fn path_loss(&self, a: usize, b: usize) -> f32 {
+        self.path_loss
+            .get(&(a.min(b), a.max(b)))
+            .copied()
+            .unwrap_or(self.default_path_loss)
+    }
+
+    /// Whether chip `id` can demodulate a transmission, based on its settings,
+    /// the time it started listening and the strength of the signal
+    fn receivable(&self, id: usize, from: usize, transmission: &Transmission) -> bool {
+        let chip = &self.chips[id];
+        id != from
+            && chip.mode() == Mode::Rx
+            && chip.mode_since() <= transmission.detect
+            && chip.channel() == transmission.channel
+            && self.received_power(from, transmission, id)
+                >= chip.noise_floor_dbm(self.noise_figure) + chip.demodulation_snr_db()
+    }
+
+    /// Whether chip `id` detected a packet that is still on the air at `at`
+    fn detected(&self, id: usize, at: u64) -> bool {
+        self.transmissions.iter().any(|(from, transmission)| {
+            transmission.detect <= at
+                && at < transmission.end
+                && self.receivable(id, *from, transmission)
+        })
+    }
+
+    /// Whether any signal was on the air on the channel of chip `id`
+    /// during channel activity detection, which ended at `at`
+    fn activity(&self, id: usize, at: u64) -> bool {
+        let chip = &self.chips[id];
+        let channel = chip.channel();
+        let threshold = chip.noise_floor_dbm(self.noise_figure) + chip.demodulation_snr_db();
+        self.transmissions.iter().any(|(from, transmission)| {
+            *from != id
+                && transmission.start < at
+                && chip.mode_since() < transmission.end
+                && transmission.channel.interferes(&channel)
+                && self.received_power(*from, transmission, id) >= threshold
+        })
+    }
+
+    /// Strongest signal on the frequency of chip `id` at `now`, or the noise floor
+    fn rssi_inst(&self, id: usize, now: u64) -> f32 {
+        let chip = &self.chips[id];
+        let channel = chip.channel();
+        self.transmissions
+            .iter()
+            .filter(|(from, transmission)| {
+                *from != id
+                    && transmission.start <= now
+                    && now < transmission.end
+                    && transmission.channel.interferes(&channel)
+            })
+            .map(|(from, transmission)| self.received_power(*from, transmission, id))
+            .fold(chip.noise_floor_dbm(self.noise_figure), f32::max)
+    }
+
+    /// Deliver the packet chip `from` finished sending at `end` to all chips that receive it.
+    /// Packets that overlap with a signal of similar strength are corrupted,
+    /// packets that overlap with a much stronger signal are lost
+    fn deliver(&mut self, from: usize, end: u64) {
+        let index = match self
+            .transmissions
+            .iter()
+            .position(|(id, transmission)| *id == from && transmission.end == end)
+        {
+            Some(index) => index,
+            None => return,
+        };
+        for id in 0..self.chips.len() {
+            let (_, transmission) = &self.transmissions[index];
+            if !self.receivable(id, from, transmission) {
+                continue;
+            }
+            let power = self.received_power(from, transmission, id);
+            let mut corrupted = false;
+            let mut lost = false;
+            for (other_from, other) in self.transmissions.iter() {
+                if *other_from == from
+                    || *other_from == id
+                    || other.end <= transmission.start
+                    || transmission.end <= other.start
+                    || !other.channel.interferes(&transmission.channel)
+                {
+                    continue;
+                }
+                let interference = self.received_power(*other_from, other, id);
+                if interference > power + CAPTURE_THRESHOLD_DB {
+                    lost = true;
+                } else if interference > power - CAPTURE_THRESHOLD_DB {
+                    corrupted = true;
+                }
+            }
+            if lost {
+                continue;
+            }
+            let chip = &self.chips[id];
+            let noise = chip.noise_floor_dbm(self.noise_figure);
+            let snr = (power - noise).round() as i8;
+            // The reported RSSI includes the noise
+            let rssi =
+                (10. * (10f32.powf(power / 10.) + 10f32.powf(noise / 10.)).log10()).round() as i16;
+            let crc_ok = !(corrupted && transmission.crc);
+            let payload = if corrupted {
+                transmission.payload.iter().map(|b| !b).collect()
+            } else {
+                transmission.payload.clone()
+            };
+            self.chips[id].receive(&payload, rssi, snr, crc_ok);
+        }
+    }
+}
+
+/// Virtual air shared by several simulated devices. The medium delivers a packet
+/// to every device that listens on the same frequency with the same modulation,
+/// sync word and IQ setup, and receives it above its sensitivity.
+/// The devices share a single clock, and a packet arrives after its time on air.
+///
+/// ```
+/// use sx126x::sim::Medium;
+///
+/// let medium = Medium::new();
+/// let (a, b) = (medium.add_device(), medium.add_device());
+/// medium.set_path_loss(&a, &b, 100.);
+/// assert_eq!(a.clock().now(), b.clock().now());
+/// ```
+#[derive(Clone)]
+pub struct Medium {
+    air: Arc<Mutex<Air>>,
+    clock: Clock,
+}
+
+impl Default for Medium {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Medium {
+    /// Create a medium with its own clock
+    pub fn new() -> Self {
+        Self::with_clock(Clock::new())
+    }
+
+    /// Create a medium driven by `clock`
+    pub fn with_clock(clock: Clock) -> Self {
+        Self {
+            air: Arc::new(Mutex::new(Air::new())),
+            clock,
+        }
+    }
+
+    pub fn clock(&self) -> Clock {
+        self.clock.clone()
+    }
+
+    /// Add a simulated device to the medium
+    pub fn add_device(&self) -> Device {
+        let mut air = super::lock(&self.air);
+        air.chips.push(Chip::new());
+        Device::new_in(self.air.clone(), air.chips.len() - 1, self.clock.clone())
+    }
+
+    /// Set the path loss in dB used between devices without a specific path loss. Defaults to 80 dB
+    pub fn set_default_path_loss(&self, loss_db: f32) {
+        super::lock(&self.air).default_path_loss = loss_db;
+    }
+
+    /// Set the path loss in dB between two devices, in both directions
+    pub fn set_path_loss(&self, a: &Device, b: &Device, loss_db: f32) {
+        assert!(
+            a.is_on(&self.air) && b.is_on(&self.air),
+            "Device is not on this medium"
+        );
+        let (a, b) = (a.id(), b.id());
+        super::lock(&self.air)
+            .path_loss
+            .insert((a.min(b), a.max(b)), loss_db);
+    }
+
+    /// Set the noise figure of the receivers in dB. Defaults to 6 dB
+    pub fn set_noise_figure(&self, noise_figure_db: f32) {
+        super::lock(&self.air).noise_figure = noise_figure_db;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{phy, Ant, Busy, Dio1, Nrst, Spi};
+    use super::*;
+    use crate::conf::Config;
+    use crate::op::irq::IrqMaskBit::{CrcErr, PreambleDetected, RxDone, Timeout};
+    use crate::op::modulation::lora::LoraModParams;
+    use crate::op::packet::lora::{LoRaCrcType, LoRaInvertIq, LoRaPacketParams};
+    use crate::op::*;
+    use crate::SX126x;
+
+    /// Simulated device, operated by the blocking driver
+    struct Node {
+        device: Device,
+        sx: SX126x<Spi, Nrst, Busy, Ant>,
+        dio1: Dio1,
+    }
+
+    impl Node {
+        fn new(medium: &Medium) -> Self {
+            let device = medium.add_device();
+            let mut sx = SX126x::new((device.nrst(), device.busy(), device.ant()));
+            sx.init(&mut device.spi(), &mut device.delay(), build_config())
+                .unwrap();
+            let dio1 = device.dio1();
+            Self { device, sx, dio1 }
+        }
+
+        fn set_packet_params(&mut self, params: LoRaPacketParams) {
+            let (spi, delay) = (&mut self.device.spi(), &mut self.device.delay());
+            self.sx
+                .set_packet_params(spi, delay, params.into())
+                .unwrap();
+        }
+
+        /// Start sending `payload`, without waiting for it to be sent
+        fn start_tx(&mut self, payload: &[u8]) {
+            let params = LoRaPacketParams::default()
+                .set_crc_type(LoRaCrcType::CrcOn)
+                .set_payload_len(payload.len() as u8);
+            self.set_packet_params(params);
+            let (spi, delay) = (&mut self.device.spi(), &mut self.device.delay());
+            self.sx.write_buffer(spi, delay, 0x00, payload).unwrap();
+            self.sx.set_tx(spi, delay, 0.into()).unwrap();
+        }
+
+        fn start_rx(&mut self) {
+            let (spi, delay) = (&mut self.device.spi(), &mut self.device.delay());
+            self.sx
+                .set_rx(spi, delay, RxTxTimeout::from_ms(1000))
+                .unwrap();
+        }
+
+        /// Whether a packet was received, and whether its CRC was invalid
+        fn received(&self) -> (bool, bool) {
+            let irq_status = self.device.irq_status();
+            (
+                irq_status & RxDone as u16 != 0,
+                irq_status & CrcErr as u16 != 0,
+            )
+        }
+
+        /// Payload of the last received packet
+        fn payload(&mut self) -> Vec<u8> {
+            let (spi, delay) = (&mut self.device.spi(), &mut self.device.delay());
+            let status = self.sx.get_rx_buffer_status(spi, delay).unwrap();
+            let mut payload = std::vec![0; status.payload_length_rx() as usize];
+            self.sx
+                .read_buffer(spi, delay, status.rx_start_buffer_pointer(), &mut payload)
+                .unwrap();
+            payload
+        }
+
+        fn channel_is_free(&mut self) -> bool {
+            let (spi, delay) = (&mut self.device.spi(), &mut self.device.delay());
+            self.sx.channel_is_free(spi, delay, &mut self.dio1).unwrap()
+        }
+
+        /// Time on air of the last packet sent
+        fn time_on_air(&self) -> u64 {
+            phy::time_on_air_ns(
+                PacketType::LoRa as u8,
+                &self.device.mod_params(),
+                &self.device.packet_params(),
+            )
+        }
+    }
+
+    fn build_config() -> Config {
+        // Sends at 14 dBm, and receives down to about -124.5 dBm using SF7 at 125 kHz
+        let pa_settings = OutputPower::dbm(14).pa_settings(Variant::SX1261).unwrap();
+        Config {
+            variant: Variant::SX1261,
+            regulator_mode: RegulatorMode::DcDc,
+            tcxo: None,
+            packet_type: PacketType::LoRa,
+            sync_word: 0x1424,
+            calib_param: CalibParam::all(),
+            mod_params: LoraModParams::default().into(),
+            tx_params: pa_settings.tx_params,
+            pa_config: pa_settings.pa_config,
+            packet_params: None,
+            dio1_irq_mask: IrqMask::all(),
+            dio2_irq_mask: IrqMask::none(),
+            dio3_irq_mask: IrqMask::none(),
+            rf_frequency: Frequency::from_hz(868_000_000),
+        }
+    }
+
+    /// Advance `clock` up to `at` ns
+    fn advance_to(clock: &Clock, at: u64) {
+        clock.advance(at.saturating_sub(clock.now()));
+    }
+
+    #[test]
+    fn ping_pong() {
+        let medium = Medium::new();
+        let clock = medium.clock();
+        let (mut a, mut b) = (Node::new(&medium), Node::new(&medium));
+
+        b.start_rx();
+        a.start_tx(b"ping");
+        let start = clock.now();
+        let time_on_air = a.time_on_air();
+
+        // The packet arrives after its time on air
+        advance_to(&clock, start + time_on_air - 100_000);
+        assert_eq!(b.received(), (false, false));
+        assert_eq!(a.device.mode(), Mode::Tx);
+        advance_to(&clock, start + time_on_air + 100_000);
+        assert_eq!(b.received(), (true, false));
+        assert_eq!(a.device.mode(), Mode::StbyRc);
+        assert_eq!(b.payload(), b"ping");
+
+        a.start_rx();
+        b.start_tx(b"pong");
+        advance_to(&clock, clock.now() + b.time_on_air() + 100_000);
+        assert_eq!(a.received(), (true, false));
+        assert_eq!(a.payload(), b"pong");
+    }
+
+    #[test]
+    fn read_bytes() {
+        let medium = Medium::new();
+        let (mut a, mut b) = (Node::new(&medium), Node::new(&medium));
+
+        // All IRQs are mapped to dio1, so PreambleDetected raises it before RxDone
+        a.start_tx(b"ping");
+        let (spi, delay) = (&mut b.device.spi(), &mut b.device.delay());
+        let mut buf = [0u8; 255];
+        let packet =
+            b.sx.read_bytes(
+                spi,
+                delay,
+                &mut buf,
+                RxTxTimeout::from_ms(1000),
+                &mut b.dio1,
+            )
+            .unwrap();
+        assert_eq!(&buf[..packet.len() as usize], b"ping");
+        assert_eq!(b.device.irq_status(), 0);
+    }
+
+    #[test]
+    fn sensitivity() {
+        let medium = Medium::new();
+        let clock = medium.clock();
+        let (mut a, mut b, mut c) = (Node::new(&medium), Node::new(&medium), Node::new(&medium));
+        medium.set_path_loss(&a.device, &b.device, 138.);
+        medium.set_path_loss(&a.device, &c.device, 139.);
+
+        b.start_rx();
+        c.start_rx();
+        a.start_tx(b"far away");
+        advance_to(&clock, clock.now() + a.time_on_air() + 100_000);
+
+        assert_eq!(b.received(), (true, false));
+        assert_eq!(b.payload(), b"far away");
+        assert_eq!(c.received(), (false, false));
+        assert_eq!(c.device.mode(), Mode::Rx);
+    }
+
+    #[test]
+    fn collision() {
+        let medium = Medium::new();
+        let clock = medium.clock();
+        let (mut a, mut b, mut c) = (Node::new(&medium), Node::new(&medium), Node::new(&medium));
+
+        // Received at the same strength, the packet sent first is corrupted
+        b.start_rx();
+        a.start_tx(b"from a");
+        c.start_tx(b"from c");
+        advance_to(&clock, clock.now() + a.time_on_air() + 100_000);
+        assert_eq!(b.received(), (true, true));
+        assert_ne!(b.payload(), b"from a");
+
+        // A much stronger packet survives, and the weaker one is lost
+        medium.set_path_loss(&a.device, &b.device, 100.);
+        let (spi, delay) = (&mut b.device.spi(), &mut b.device.delay());
+        b.sx.clear_irq_status(spi, delay, IrqMask::all()).unwrap();
+        b.start_rx();
+        a.start_tx(b"from a");
+        c.start_tx(b"from c");
+        advance_to(&clock, clock.now() + a.time_on_air() + 100_000);
+        assert_eq!(b.received(), (true, false));
+        assert_eq!(b.payload(), b"from c");
+    }
+
+    #[test]
+    fn collision_during_held_rx() {
+        let medium = Medium::new();
+        let clock = medium.clock();
+        let (mut a, mut b, mut c) = (Node::new(&medium), Node::new(&medium), Node::new(&medium));
+        // C interferes, but uses another sync word, so B cannot receive it
+        let (spi, delay) = (&mut c.device.spi(), &mut c.device.delay());
+        c.sx.set_sync_word(spi, delay, 0x3444).unwrap();
+        medium.set_path_loss(&b.device, &c.device, 60.);
+
+        let (spi, delay) = (&mut b.device.spi(), &mut b.device.delay());
+        b.sx.set_rx(spi, delay, RxTxTimeout::from_ms(20)).unwrap();
+        a.start_tx(&[0x55; 64]);
+        let end = clock.now() + a.time_on_air();
+
+        // The preamble of A is detected, which stops the RX timeout
+        advance_to(&clock, clock.now() + 30_000_000);
+        assert_eq!(b.device.mode(), Mode::Rx);
+        assert_ne!(b.device.irq_status() & PreambleDetected as u16, 0);
+
+        // The much stronger packet of C makes B lose the packet of A, and B times out
+        c.start_tx(b"from c");
+        advance_to(&clock, end + 100_000);
+        assert_eq!(b.received(), (false, false));
+        assert_eq!(b.device.mode(), Mode::StbyRc);
+        assert_ne!(b.device.irq_status() & Timeout as u16, 0);
+    }
+
+    #[test]
+    fn channel_activity() {
+        let medium = Medium::new();
+        let clock = medium.clock();
+        let (mut a, mut b) = (Node::new(&medium), Node::new(&medium));
+
+        assert!(b.channel_is_free());
+        a.start_tx(&[0x55; 64]);
+        let end = clock.now() + a.time_on_air();
+        assert!(!b.channel_is_free());
+
+        // Other sync words are detected as well
+        let (spi, delay) = (&mut b.device.spi(), &mut b.device.delay());
+        b.sx.set_sync_word(spi, delay, 0x3444).unwrap();
+        assert!(!b.channel_is_free());
+
+        advance_to(&clock, end + 100_000);
+        assert!(b.channel_is_free());
+    }
+
+    #[test]
+    fn rssi_inst() {
+        let medium = Medium::new();
+        let (mut a, mut b) = (Node::new(&medium), Node::new(&medium));
+        medium.set_path_loss(&a.device, &b.device, 100.);
+
+        // The noise floor of SF7 at 125 kHz, rounded down to a 0.5 dBm step
+        b.start_rx();
+        let (spi, delay) = (&mut b.device.spi(), &mut b.device.delay());
+        assert_eq!(b.sx.get_rssi_inst(spi, delay).unwrap(), -117.);
+
+        // Sent at 14 dBm
+        a.start_tx(&[0x55; 64]);
+        medium.clock().advance(1_000_000);
+        let (spi, delay) = (&mut b.device.spi(), &mut b.device.delay());
+        assert_eq!(b.sx.get_rssi_inst(spi, delay).unwrap(), -86.);
+    }
+
+    #[test]
+    fn channel_mismatch() {
+        let medium = Medium::new();
+        let clock = medium.clock();
+        let mut a = Node::new(&medium);
+        let mut nodes = [Node::new(&medium), Node::new(&medium), Node::new(&medium)];
+        let (spi, delay) = (&mut nodes[1].device.spi(), &mut nodes[1].device.delay());
+        nodes[1].sx.set_sync_word(spi, delay, 0x3444).unwrap();
+        nodes[2]
+            .set_packet_params(LoRaPacketParams::default().set_invert_iq(LoRaInvertIq::Inverted));
+
+        for node in nodes.iter_mut() {
+            node.start_rx();
+        }
+        a.start_tx(b"hello");
+        advance_to(&clock, clock.now() + a.time_on_air() + 100_000);
+
+        let received: Vec<_> = nodes.iter().map(|node| node.received().0).collect();
+        assert_eq!(received, [true, false, false]);
+    }
+}
@@ -0,0 +1,373 @@
+//! Software model of the SX126x, to test application logic on a host without hardware.
+//! Enable the `sim` feature to use it.
+//!
+//! A simulated [`Device`] hands out an SPI device and pins implementing the same
+//! embedded-hal traits that `SX126x::new` and `SX126x::init` consume. The model
+//! decodes the commands clocked in over SPI, maintains the buffer, registers, chip mode
+//! and IRQ status, and drives the busy and dio1 pins accordingly.
+//!
+//! Time is virtual: it only advances through the [`Delay`] obtained from the
+//! device's [`Clock`], which makes tests fast and deterministic.
+//!
+//! Several devices can exchange packets through a [`Medium`], which models
+//! path loss, RSSI and SNR, collisions and the time on air of each packet.
+//!
+//! ```ignore
+//! let device = sim::Device::new();
+//! let mut sx = SX126x::new((device.nrst(), device.busy(), device.ant()));
+//! sx.init(&mut device.spi(), &mut device.delay(), conf)?;
+//! ```
+mod chip;
+mod medium;
+mod phy;
+
+use core::convert::Infallible;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::vec::Vec;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{self, InputPin, OutputPin};
+use embedded_hal::spi::{self, Operation, SpiDevice};
+
+use self::chip::Chip;
+use self::medium::Air;
+pub use self::medium::Medium;
+
+const NOP: u8 = 0x00;
+
+/// Mode the simulated chip is in
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Sleep,
+    StbyRc,
+    StbyXosc,
+    Fs,
+    Tx,
+    Rx,
+    Cad,
+}
+
+/// Virtual clock in ns, shared by the simulated devices and delays created from it
+#[derive(Clone, Debug, Default)]
+pub struct Clock {
+    now_ns: Arc<AtomicU64>,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current time in ns
+    pub fn now(&self) -> u64 {
+        self.now_ns.load(Ordering::SeqCst)
+    }
+
+    /// Advance the clock by `ns`
+    pub fn advance(&self, ns: u64) {
+        self.now_ns.fetch_add(ns, Ordering::SeqCst);
+    }
+
+    /// Create a delay that advances this clock
+    pub fn delay(&self) -> Delay {
+        Delay {
+            clock: self.clone(),
+        }
+    }
+}
+
+/// Delay that advances the virtual clock instead of sleeping
+#[derive(Clone, Debug)]
+pub struct Delay {
+    clock: Clock,
+}
+
+impl DelayNs for Delay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.clock.advance(ns as u64);
+    }
+}
+
+/// Simulated SX126x. Cloning it yields another handle to the same device
+#[derive(Clone)]
+pub struct Device {
+    air: Arc<Mutex<Air>>,
+    id: usize,
+    clock: Clock,
+}
+
+impl Default for Device {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Device {
+    /// Create a device with its own clock, on a medium of its own
+    pub fn new() -> Self {
+        Medium::new().add_device()
+    }
+
+    /// Create a device driven by `clock`, on a medium of its own
+    pub fn with_clock(clock: Clock) -> Self {
+        Medium::with_clock(clock).add_device()
+    }
+
+    fn new_in(air: Arc<Mutex<Air>>, id: usize, clock: Clock) -> Self {
+        Self { air, id, clock }
+    }
+
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn is_on(&self, air: &Arc<Mutex<Air>>) -> bool {
+        Arc::ptr_eq(&self.air, air)
+    }
+
+    pub fn clock(&self) -> Clock {
+        self.clock.clone()
+    }
+
+    /// Create a delay that advances the clock of this device
+    pub fn delay(&self) -> Delay {
+        self.clock.delay()
+    }
+
+    /// SPI device connected to this chip
+    pub fn spi(&self) -> Spi {
+        Spi {
+            device: self.clone(),
+        }
+    }
+
+    pub fn nrst(&self) -> Nrst {
+        Nrst {
+            device: self.clone(),
+        }
+    }
+
+    pub fn busy(&self) -> Busy {
+        Busy {
+            device: self.clone(),
+        }
+    }
+
+    pub fn dio1(&self) -> Dio1 {
+        Dio1 {
+            device: self.clone(),
+        }
+    }
+
+    pub fn ant(&self) -> Ant {
+        Ant {
+            device: self.clone(),
+        }
+    }
+
+    /// Current chip mode
+    pub fn mode(&self) -> Mode {
+        self.with_chip(|chip| chip.mode())
+    }
+
+    /// Current IRQ status
+    pub fn irq_status(&self) -> u16 {
+        self.with_chip(|chip| chip.irq_status())
+    }
+
+    /// Value of the register at `addr`
+    pub fn register(&self, addr: u16) -> u8 {
+        self.with_chip(|chip| chip.register(addr))
+    }
+
+    /// Contents of the data buffer
+    pub fn buffer(&self) -> [u8; 256] {
+        self.with_chip(|chip| chip.buffer())
+    }
+
+    /// Last rf_freq value passed to SetRfFrequency
+    pub fn rf_freq(&self) -> u32 {
+        self.with_chip(|chip| chip.rf_freq())
+    }
+
+    /// Last packet type passed to SetPacketType
+    pub fn packet_type(&self) -> u8 {
+        self.with_chip(|chip| chip.packet_type())
+    }
+
+    /// Last raw params passed to SetModulationParams
+    pub fn mod_params(&self) -> [u8; 8] {
+        self.with_chip(|chip| chip.mod_params())
+    }
+
+    /// Last raw params passed to SetPacketParams
+    pub fn packet_params(&self) -> [u8; 9] {
+        self.with_chip(|chip| chip.packet_params())
+    }
+
+    /// Whether the ant pin is high
+    pub fn ant_enabled(&self) -> bool {
+        self.with_chip(|chip| chip.ant_enabled())
+    }
+
+    /// Set whether channel activity detection reports activity
+    pub fn set_channel_active(&self, active: bool) {
+        self.with_chip(|chip| chip.set_channel_active(active));
+    }
+
+    /// Take the payloads transmitted since the last call
+    pub fn take_transmitted(&self) -> Vec<Vec<u8>> {
+        self.with_chip(|chip| chip.take_transmitted())
+    }
+
+    /// Deliver a packet to the chip, as if it was received with the passed RSSI in dBm
+    /// and SNR in dB. Returns false if the chip was not in RX mode.
+    pub fn receive(&self, payload: &[u8], rssi: i16, snr: i8) -> bool {
+        self.with_chip(|chip| chip.receive(payload, rssi, snr, true))
+    }
+
+    /// Access the chip after completing any operation on the medium that is due
+    fn with_chip<R>(&self, f: impl FnOnce(&mut Chip) -> R) -> R {
+        let mut air = lock(&self.air);
+        air.update(self.clock.now());
+        f(&mut air.chips[self.id])
+    }
+}
+
+fn lock(air: &Mutex<Air>) -> MutexGuard<'_, Air> {
+    air.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// SPI device connected to a simulated chip. Each transaction is framed by nss
+pub struct Spi {
+    device: Device,
+}
+
+impl spi::ErrorType for Spi {
+    type Error = Infallible;
+}
+
+impl SpiDevice for Spi {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        let clock = &self.device.clock;
+        let mut air = lock(&self.device.air);
+        air.update(clock.now());
+        let chip = &mut air.chips[self.device.id];
+        chip.select(clock.now());
+        for operation in operations.iter_mut() {
+            match operation {
+                Operation::Read(words) => {
+                    for word in words.iter_mut() {
+                        *word = chip.transfer(NOP);
+                    }
+                }
+                Operation::Write(words) => {
+                    for word in words.iter() {
+                        chip.transfer(*word);
+                    }
+                }
+                Operation::Transfer(read, write) => {
+                    for i in 0..read.len().max(write.len()) {
+                        let word = chip.transfer(write.get(i).copied().unwrap_or(NOP));
+                        if let Some(read) = read.get_mut(i) {
+                            *read = word;
+                        }
+                    }
+                }
+                Operation::TransferInPlace(words) => {
+                    for word in words.iter_mut() {
+                        *word = chip.transfer(*word);
+                    }
+                }
+                Operation::DelayNs(ns) => clock.advance(*ns as u64),
+            }
+        }
+        chip.deselect(clock.now());
+        air.collect(self.device.id);
+        Ok(())
+    }
+}
+
+/// Reset pin of a simulated chip
+pub struct Nrst {
+    device: Device,
+}
+
+impl digital::ErrorType for Nrst {
+    type Error = Infallible;
+}
+
+impl OutputPin for Nrst {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        let now = self.device.clock.now();
+        self.device.with_chip(|chip| chip.set_nrst(now, false));
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        let now = self.device.clock.now();
+        self.device.with_chip(|chip| chip.set_nrst(now, true));
+        Ok(())
+    }
+}
+
+/// Busy pin of a simulated chip
+pub struct Busy {
+    device: Device,
+}
+
+impl digital::ErrorType for Busy {
+    type Error = Infallible;
+}
+
+impl InputPin for Busy {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        let now = self.device.clock.now();
+        Ok(self.device.with_chip(|chip| chip.busy(now)))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_high().map(|high| !high)
+    }
+}
+
+/// Dio1 pin of a simulated chip, high while any IRQ mapped to dio1 is set
+pub struct Dio1 {
+    device: Device,
+}
+
+impl digital::ErrorType for Dio1 {
+    type Error = Infallible;
+}
+
+impl InputPin for Dio1 {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.device.with_chip(|chip| chip.dio1()))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_high().map(|high| !high)
+    }
+}
+
+/// Antenna enable pin of a simulated chip
+pub struct Ant {
+    device: Device,
+}
+
+impl digital::ErrorType for Ant {
+    type Error = Infallible;
+}
+
+impl OutputPin for Ant {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.device.with_chip(|chip| chip.set_ant(false));
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.device.with_chip(|chip| chip.set_ant(true));
+        Ok(())
+    }
+}
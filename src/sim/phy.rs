@@ -0,0 +1,165 @@
+//! Physical layer calculations based on the raw modulation and packet params
+use crate::op::XTAL_HZ;
+
+const PACKET_TYPE_LORA: u8 = 0x01;
+
+/// Minimum number of preamble symbols a LoRa receiver needs to detect a packet
+const LORA_DETECT_SYMBOLS: u64 = 4;
+
+/// Thermal noise density in dBm/Hz
+const THERMAL_NOISE_DBM_HZ: f32 = -174.;
+
+/// LoRa bandwidth in Hz, see 13.4.5.2
+pub(crate) fn lora_bandwidth_hz(code: u8) -> u64 {
+    match code {
+        0x00 => 7_810,
+        0x08 => 10_420,
+        0x01 => 15_630,
+        0x09 => 20_830,
+        0x02 => 31_250,
+        0x0A => 41_670,
+        0x03 => 62_500,
+        0x05 => 250_000,
+        0x06 => 500_000,
+        _ => 125_000,
+    }
+}
+
+/// GFSK RX bandwidth in Hz, see 13.4.5.1
+fn gfsk_bandwidth_hz(code: u8) -> u64 {
+    match code {
+        0x1F => 4_800,
+        0x17 => 5_800,
+        0x0F => 7_300,
+        0x1E => 9_700,
+        0x16 => 11_700,
+        0x0E => 14_600,
+        0x1D => 19_500,
+        0x15 => 23_400,
+        0x0D => 29_300,
+        0x1C => 39_000,
+        0x14 => 46_900,
+        0x0C => 58_600,
+        0x1B => 78_200,
+        0x13 => 93_800,
+        0x0B => 117_300,
+        0x1A => 156_200,
+        0x12 => 187_200,
+        0x0A => 234_300,
+        0x19 => 312_000,
+        0x11 => 373_600,
+        _ => 467_000,
+    }
+}
+
+fn spreading_factor(mod_params: &[u8; 8]) -> u64 {
+    mod_params[0].clamp(5, 12) as u64
+}
+
+/// LoRa symbol time in ns
+pub(crate) fn lora_symbol_time_ns(mod_params: &[u8; 8]) -> u64 {
+    (1_000_000_000 << spreading_factor(mod_params)) / lora_bandwidth_hz(mod_params[1])
+}
+
+/// GFSK bit time in ns. 13.4.5.1: BR = 32 * Fxtal / bit rate
+fn gfsk_bit_time_ns(mod_params: &[u8; 8]) -> u64 {
+    let br = u32::from_be_bytes([0, mod_params[0], mod_params[1], mod_params[2]]) as u64;
+    (br * 1_000_000_000 / (32 * XTAL_HZ as u64)).max(1)
+}
+
+/// Time on air of a packet in ns, see 6.1.4 for LoRa and 6.2.3 for GFSK
+pub(crate) fn time_on_air_ns(
+    packet_type: u8,
+    mod_params: &[u8; 8],
+    packet_params: &[u8; 9],
+) -> u64 {
+    let preamble_len = u16::from_be_bytes([packet_params[0], packet_params[1]]) as i64;
+    if packet_type == PACKET_TYPE_LORA {
+        let sf = spreading_factor(mod_params) as i64;
+        let coding_rate = match mod_params[2] {
+            0x05 => 1,
+            0x06 => 2,
+            0x07 => 4,
+            cr => cr.clamp(1, 4) as i64,
+        };
+        let low_dr_opt = mod_params[3] & 0x01 == 1;
+        let explicit_header = packet_params[2] == 0x00;
+        let payload_len = packet_params[3] as i64;
+        let crc = packet_params[4] == 0x01;
+
+        // Symbols are counted in quarters, as the preamble is followed by 4.25 or 6.25 symbols
+        let (preamble_quarters, mut bits, divisor) = if sf < 7 {
+            (preamble_len * 4 + 25, 0, 4 * sf)
+        } else if low_dr_opt {
+            (preamble_len * 4 + 17, 8, 4 * (sf - 2))
+        } else {
+            (preamble_len * 4 + 17, 8, 4 * sf)
+        };
+        bits += 8 * payload_len + 16 * crc as i64 - 4 * sf;
+        if explicit_header {
+            bits += 20;
+        }
+        let payload_symbols = 8 + (bits.max(0) + divisor - 1) / divisor * (coding_rate + 4);
+        let quarters = (preamble_quarters + 4 * payload_symbols) as u64;
+        quarters * lora_symbol_time_ns(mod_params) / 4
+    } else {
+        let sync_word_len = packet_params[3] as i64;
+        let addr_comp = packet_params[4] != 0x00;
+        let variable_len = packet_params[5] == 0x01;
+        let payload_len = packet_params[6] as i64;
+        let crc_len = match packet_params[7] {
+            0x00 | 0x04 => 8,
+            0x02 | 0x06 => 16,
+            _ => 0,
+        };
+        let bits = preamble_len
+            + sync_word_len
+            + 8 * variable_len as i64
+            + 8 * addr_comp as i64
+            + 8 * payload_len
+            + crc_len;
+        bits as u64 * gfsk_bit_time_ns(mod_params)
+    }
+}
+
+/// Time in ns after the start of a packet until which a receiver can start
+/// listening and still detect the preamble
+pub(crate) fn preamble_detect_ns(
+    packet_type: u8,
+    mod_params: &[u8; 8],
+    packet_params: &[u8; 9],
+) -> u64 {
+    let preamble_len = u16::from_be_bytes([packet_params[0], packet_params[1]]) as u64;
+    if packet_type == PACKET_TYPE_LORA {
+        preamble_len.saturating_sub(LORA_DETECT_SYMBOLS) * lora_symbol_time_ns(mod_params)
+    } else {
+        let detector_len = match packet_params[2] {
+            0x04 => 8,
+            0x05 => 16,
+            0x06 => 24,
+            0x07 => 32,
+            _ => 0,
+        };
+        preamble_len.saturating_sub(detector_len) * gfsk_bit_time_ns(mod_params)
+    }
+}
+
+/// Noise floor in dBm within the receiver bandwidth
+pub(crate) fn noise_floor_dbm(packet_type: u8, mod_params: &[u8; 8], noise_figure: f32) -> f32 {
+    let bandwidth_hz = if packet_type == PACKET_TYPE_LORA {
+        lora_bandwidth_hz(mod_params[1])
+    } else {
+        gfsk_bandwidth_hz(mod_params[4])
+    };
+    THERMAL_NOISE_DBM_HZ + 10. * (bandwidth_hz as f32).log10() + noise_figure
+}
+
+/// Minimum SNR in dB needed to demodulate a packet. LoRa can demodulate
+/// below the noise floor, 2.5 dB less for every step in spreading factor
+pub(crate) fn demodulation_snr_db(packet_type: u8, mod_params: &[u8; 8]) -> f32 {
+    if packet_type == PACKET_TYPE_LORA {
+        -2.5 * (spreading_factor(mod_params) as f32 - 4.)
+    } else {
+        10.
+    }
+}
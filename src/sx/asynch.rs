@@ -0,0 +1,142 @@
+//! Async variant of the SX126x driver, built on embedded-hal-async.
+//! Instead of busily waiting on the busy and dio1 pins, it awaits their
+//! level changes, so the executor can sleep while the modem is working.
+//! It has the same API as the blocking driver, with async methods.
+//! On top of that, [`transmit`](SX126x::transmit), [`receive`](SX126x::receive)
+//! and [`cad`](SX126x::cad) send a packet, receive a packet and detect channel activity,
+//! awaiting dio1 until the modem is done.
+//! Enable the `async` feature to use it.
+use core::future::{poll_fn, Future};
+use core::marker::PhantomData;
+use core::pin::pin;
+use core::task::Poll;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::spi::{Operation, SpiDevice};
+
+pub use super::driver::Dio1Timeout;
+use super::driver::State;
+use super::err::{PinError, RxError, SpiError, SxError, WaitFor};
+use super::NOP;
+use crate::conf::Config;
+use crate::op::*;
+use crate::reg::*;
+
+type Pins<TNRST, TBUSY, TANT> = (TNRST, TBUSY, TANT);
+
+/// Async wrapper around a Semtech SX1261/62 LoRa modem.
+/// The modem is accessed through an SPI device, which takes care of the nss pin
+pub struct SX126x<TSPI, TNRST, TBUSY, TANT> {
+    spi: PhantomData<TSPI>,
+    nrst_pin: TNRST,
+    busy_pin: TBUSY,
+    ant_pin: TANT,
+    state: State,
+}
+
+impl_sx126x!(async, await, Wait);
+
+impl<TSPI, TNRST, TBUSY, TANT, TSPIERR, TPINERR> SX126x<TSPI, TNRST, TBUSY, TANT>
+where
+    TPINERR: core::fmt::Debug,
+    TSPI: SpiDevice<Error = TSPIERR>,
+    TNRST: OutputPin<Error = TPINERR>,
+    TBUSY: Wait<Error = TPINERR>,
+    TANT: OutputPin<Error = TPINERR>,
+{
+    /// Reset the device py pulling nrst low for a while
+    pub async fn reset(&mut self, delay: &mut impl DelayNs) -> Result<(), PinError<TPINERR>> {
+        self.state.reset();
+        self.nrst_pin.set_low().map_err(PinError::Output)?;
+        // 8.1: The pin should be held low for typically 100 μs for the Reset to happen
+        delay.delay_us(200).await;
+        self.nrst_pin.set_high().map_err(PinError::Output)
+    }
+
+    /// Send `data`, and await TxDone or a timeout. See [`write_bytes`](Self::write_bytes)
+    #[allow(clippy::too_many_arguments)]
+    pub async fn transmit<TDIO1: Wait<Error = TPINERR>>(
+        &mut self,
+        spi: &mut TSPI,
+        delay: &mut impl DelayNs,
+        data: &[u8],
+        timeout: RxTxTimeout,
+        preamble_len: u16,
+        crc_type: packet::lora::LoRaCrcType,
+        dio1_pin: &mut TDIO1,
+    ) -> Result<Status, SxError<TSPIERR, TPINERR>> {
+        self.write_bytes(spi, delay, data, timeout, preamble_len, crc_type, dio1_pin)
+            .await
+    }
+
+    /// Receive a packet into `buf`, and await RxDone or a timeout. See [`read_bytes`](Self::read_bytes)
+    pub async fn receive<TDIO1: Wait<Error = TPINERR>>(
+        &mut self,
+        spi: &mut TSPI,
+        delay: &mut impl DelayNs,
+        buf: &mut [u8],
+        timeout: RxTxTimeout,
+        dio1_pin: &mut TDIO1,
+    ) -> Result<RxPacket, SxError<TSPIERR, TPINERR>> {
+        self.read_bytes(spi, delay, buf, timeout, dio1_pin).await
+    }
+
+    /// Run channel activity detection, and await CadDone. Returns whether
+    /// activity was detected. See [`channel_is_free`](Self::channel_is_free)
+    pub async fn cad<TDIO1: Wait<Error = TPINERR>>(
+        &mut self,
+        spi: &mut TSPI,
+        delay: &mut impl DelayNs,
+        dio1_pin: &mut TDIO1,
+    ) -> Result<bool, SxError<TSPIERR, TPINERR>> {
+        Ok(!self.channel_is_free(spi, delay, dio1_pin).await?)
+    }
+
+    /// Wait for the busy pin to go low, or until the busy timeout elapses
+    async fn wait_on_busy(
+        &mut self,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+        // 8.3.1: The max value for T SW from NSS rising edge to the BUSY rising edge is, in all cases, 600 ns
+        delay.delay_ns(600).await;
+        let busy = self.busy_pin.wait_for_low();
+        with_timeout(delay, self.state.busy_timeout, WaitFor::Busy, busy).await
+    }
+
+    /// Wait for the dio1 pin to go high, or until `timeout_us` elapses
+    async fn wait_on_dio1<TDIO1: Wait<Error = TPINERR>>(
+        &mut self,
+        delay: &mut impl DelayNs,
+        dio1_pin: &mut TDIO1,
+        timeout_us: Option<u32>,
+    ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+        let dio1 = dio1_pin.wait_for_high();
+        with_timeout(delay, timeout_us, WaitFor::Dio1, dio1).await
+    }
+}
+
+/// Await `wait`, or until `timeout_us` elapses. Pin read errors are propagated
+async fn with_timeout<TSPIERR, TPINERR>(
+    delay: &mut impl DelayNs,
+    timeout_us: Option<u32>,
+    waiting_for: WaitFor,
+    wait: impl Future<Output = Result<(), TPINERR>>,
+) -> Result<(), SxError<TSPIERR, TPINERR>> {
+    let mut wait = pin!(wait);
+    let timeout_us = match timeout_us {
+        Some(timeout_us) => timeout_us,
+        None => return Ok(wait.await.map_err(PinError::Input)?),
+    };
+    let mut timeout = pin!(delay.delay_us(timeout_us));
+    poll_fn(|cx| {
+        if let Poll::Ready(result) = wait.as_mut().poll(cx) {
+            return Poll::Ready(Ok(result.map_err(PinError::Input)?));
+        }
+        timeout
+            .as_mut()
+            .poll(cx)
+            .map(|()| Err(SxError::Timeout { waiting_for }))
+    })
+    .await
+}
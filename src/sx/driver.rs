@@ -0,0 +1,1358 @@
+//! State and methods shared by the blocking and the async driver.
+//!
+//! Both drivers implement the same API, which is generated from a single
+//! definition by `impl_sx126x!`. Every command is encoded as an op::Command
+//! and sent using SX126x::execute, so that the drivers only differ in
+//! how they talk to the SPI device and wait on the busy and dio1 pins.
+use crate::conf::{Config, ConfigError};
+use crate::op::{CalibImageFreq, PacketType, RxTxTimeout, SleepConfig, Variant};
+use crate::reg::OcpConfiguration;
+
+use super::errata::Errata;
+
+/// Default maximum time in μs to wait for the busy pin to go low.
+/// Calibrating all blocks takes about 3.5 ms, this leaves a generous margin
+const DEFAULT_BUSY_TIMEOUT_US: u32 = 100_000;
+
+/// Margin in μs added to the modem timeout by Dio1Timeout::Auto
+const DIO1_TIMEOUT_MARGIN_US: u32 = 10_000;
+
+/// Timeout in μs used by Dio1Timeout::Auto when the modem itself has no timeout
+const DIO1_FALLBACK_TIMEOUT_US: u32 = 10_000_000;
+
+/// Maximum time to wait for the dio1 pin to go high in the high level methods,
+/// before failing with SxError::Timeout
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Dio1Timeout {
+    /// Derived from the RX or TX timeout passed to the modem: the modem timeout plus
+    /// a sixteenth of it and 10 ms, to allow for clock drift and the IRQ latency.
+    /// When the modem has no timeout, as for a timeout of 0 or 0xFFFFFF and
+    /// for channel activity detection, 10 s is used instead
+    Auto,
+    /// Timeout in μs
+    Us(u32),
+    /// Wait indefinitely
+    Never,
+}
+
+impl Dio1Timeout {
+    /// Timeout in μs when the modem was started with `timeout`,
+    /// None to wait indefinitely
+    pub(crate) fn timeout_us(self, timeout: Option<RxTxTimeout>) -> Option<u32> {
+        match self {
+            Self::Auto => {
+                let steps = timeout.map_or(0, u32::from);
+                if steps == 0 || steps == 0xFF_FFFF {
+                    return Some(DIO1_FALLBACK_TIMEOUT_US);
+                }
+                // Steps of 15.625 μs
+                let timeout_us = steps * 125 / 8;
+                Some(timeout_us + timeout_us / 16 + DIO1_TIMEOUT_MARGIN_US)
+            }
+            Self::Us(timeout_us) => Some(timeout_us),
+            Self::Never => None,
+        }
+    }
+}
+
+/// State kept by the driver
+pub(crate) struct State {
+    /// Last known configuration, re-applied after waking up from a cold start
+    pub conf: Option<Config>,
+    /// Chip variant passed to SX126x::init, which parameters are checked against
+    pub variant: Option<Variant>,
+    /// Current packet type of the modem
+    pub packet_type: PacketType,
+    /// Sleep configuration, set while the modem is asleep
+    pub sleep_config: Option<SleepConfig>,
+    /// Maximum time in μs to wait for the busy pin to go low
+    pub busy_timeout: Option<u32>,
+    /// Maximum time to wait for the dio1 pin to go high
+    pub dio1_timeout: Dio1Timeout,
+    /// Settings that determine which datasheet workarounds apply
+    pub errata: Errata,
+    /// Band of the last image calibration, redone by set_rf_frequency
+    /// when the frequency moves out of it
+    pub calib_image_freq: Option<CalibImageFreq>,
+    /// Over current protection level set by set_output_power. SetPaConfig resets
+    /// the register, so it is restored along with the PA config
+    pub ocp: Option<OcpConfiguration>,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self {
+            conf: None,
+            variant: None,
+            // 13.4.2: GFSK is the default packet type after reset
+            packet_type: PacketType::GFSK,
+            sleep_config: None,
+            busy_timeout: Some(DEFAULT_BUSY_TIMEOUT_US),
+            dio1_timeout: Dio1Timeout::Auto,
+            errata: Errata::new(),
+            calib_image_freq: None,
+            ocp: None,
+        }
+    }
+
+    /// Update the state after resetting the modem
+    pub fn reset(&mut self) {
+        self.sleep_config = None;
+        self.packet_type = PacketType::GFSK;
+        self.ocp = None;
+    }
+
+    /// Check a parameter using `check` against the chip variant,
+    /// once it is known
+    pub fn check(
+        &self,
+        check: impl FnOnce(Variant) -> Result<(), ConfigError>,
+    ) -> Result<(), ConfigError> {
+        match self.variant {
+            Some(variant) => check(variant),
+            None => Ok(()),
+        }
+    }
+
+    /// Update the last known configuration, so that the change
+    /// survives a cold start
+    pub fn update_conf(&mut self, f: impl FnOnce(&mut Config)) {
+        if let Some(conf) = &mut self.conf {
+            f(conf);
+        }
+    }
+}
+
+/// Implements the SX126x API on a driver struct with `spi`, `nrst_pin`,
+/// `busy_pin`, `ant_pin` and `state` fields. The blocking driver passes
+/// nothing for `$async` and `$await`, the async driver passes `async` and `await`,
+/// which turns every method into an async fn and awaits every call to another one.
+/// `$pin` is the trait implemented by the busy and dio1 pins.
+///
+/// The invoking module provides `wait_on_busy`, `wait_on_dio1` and `reset`,
+/// and imports the SpiDevice, Operation and DelayNs traits to use.
+macro_rules! impl_sx126x {
+    ($($async:ident)?, $($await:ident)?, $pin:ident) => {
+        impl<TSPI, TNRST, TBUSY, TANT, TSPIERR, TPINERR> SX126x<TSPI, TNRST, TBUSY, TANT>
+        where
+            TPINERR: core::fmt::Debug,
+            TSPI: SpiDevice<Error = TSPIERR>,
+            TNRST: OutputPin<Error = TPINERR>,
+            TBUSY: $pin<Error = TPINERR>,
+            TANT: OutputPin<Error = TPINERR>,
+        {
+            // Create a new SX126x
+            pub fn new(pins: Pins<TNRST, TBUSY, TANT>) -> Self {
+                let (nrst_pin, busy_pin, ant_pin) = pins;
+                Self {
+                    spi: PhantomData,
+                    nrst_pin,
+                    busy_pin,
+                    ant_pin,
+                    state: State::new(),
+                }
+            }
+
+            /// Set the maximum time in μs to wait for the busy pin to go low
+            /// before failing with SxError::Timeout. Defaults to 100 ms.
+            /// Pass None to wait indefinitely
+            pub fn set_busy_timeout(&mut self, timeout_us: Option<u32>) {
+                self.state.busy_timeout = timeout_us;
+            }
+
+            /// Set the maximum time to wait for the dio1 pin to go high
+            /// in the high level methods before failing with SxError::Timeout.
+            /// Defaults to Dio1Timeout::Auto, which follows the RX or TX timeout
+            /// passed to the modem
+            pub fn set_dio1_timeout(&mut self, timeout: Dio1Timeout) {
+                self.state.dio1_timeout = timeout;
+            }
+
+            /// Enable or disable the workarounds for the limitations described in
+            /// chapter 15 of the datasheet. These are enabled by default:
+            /// - 15.1: set_mod_params optimizes the modulation quality for LoRa with a bandwidth of 500 kHz
+            /// - 15.2: set_pa_config raises the PA clamping threshold when using the high power PA
+            /// - 15.3: The RTC is stopped after an RX timeout when receiving LoRa packets with an implicit header
+            /// - 15.4: set_packet_params optimizes the IQ polarity setup for inverted IQ
+            pub fn set_errata_workarounds(&mut self, enabled: bool) {
+                self.state.errata.enabled = enabled;
+            }
+
+            // Initialize and configure the SX126x using the provided Config,
+            // after validating it against its chip variant
+            pub $($async)? fn init(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                conf: Config,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                conf.validate()?;
+                self.state.variant = Some(conf.variant);
+
+                // Reset the sx
+                self.reset(delay)$(.$await)??;
+
+                self.configure(spi, delay, &conf)$(.$await)??;
+                self.state.conf = Some(conf);
+                Ok(())
+            }
+
+            /// Apply the configuration to the modem, as described in 14.2 and 14.3.
+            /// As this is called by wake_up, it only uses methods that do not wake the modem
+            $($async)? fn configure(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                conf: &Config,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                // 1. If not in STDBY_RC mode, then go to this mode with the command SetStandby(...)
+                let standby_config = StandbyConfig::StbyRc as u8;
+                self.transfer(spi, delay, Command::SetStandby { standby_config }, &mut [])
+                    $(.$await)??;
+
+                // The regulator and the TCXO are set up before calibrating, as the
+                // 32 MHz oscillator cannot start without its TCXO powered
+                let mode = conf.regulator_mode as u8;
+                self.transfer(spi, delay, Command::SetRegulatorMode { mode }, &mut [])
+                    $(.$await)??;
+                if let Some(tcxo) = conf.tcxo {
+                    let command = Command::SetDio3AsTcxoCtrl {
+                        tcxo_voltage: tcxo.voltage as u8,
+                        tcxo_delay: tcxo.delay.into(),
+                    };
+                    self.transfer(spi, delay, command, &mut [])$(.$await)??;
+                    // 13.3.6: Clear the XOSC start error raised while the TCXO was not powered
+                    self.transfer(spi, delay, Command::ClearDeviceErrors, &mut [])
+                        $(.$await)??;
+                }
+
+                // 2. Define the protocol (LoRa® or FSK) with the command SetPacketType(...)
+                self.apply_packet_type(spi, delay, conf.packet_type)$(.$await)??;
+
+                // 3. Define the RF frequency with the command SetRfFrequency(...)
+                // The modem has just been reset, so the image is calibrated below
+                self.state.calib_image_freq = None;
+                self.apply_rf_frequency(spi, delay, conf.rf_frequency)$(.$await)??;
+
+                // Calibrate
+                let calib_param = conf.calib_param.into();
+                self.transfer(spi, delay, Command::Calibrate { calib_param }, &mut [])
+                    $(.$await)??;
+                let freq = CalibImageFreq::from_rf_frequency(conf.rf_frequency.hz());
+                self.apply_calibrate_image(spi, delay, freq)$(.$await)??;
+
+                // 4. Define the Power Amplifier configuration with the command SetPaConfig(...)
+                self.apply_pa_config(spi, delay, conf.pa_config)$(.$await)??;
+
+                // 5. Define output power and ramping time with the command SetTxParams(...)
+                self.apply_tx_params(spi, delay, conf.tx_params)$(.$await)??;
+
+                // 6. Define where the data payload will be stored with the command SetBufferBaseAddress(...)
+                let command = Command::SetBufferBaseAddress {
+                    tx_base_addr: 0x00,
+                    rx_base_addr: 0x00,
+                };
+                self.transfer(spi, delay, command, &mut [])$(.$await)??;
+
+                // 7. Send the payload to the data buffer with the command WriteBuffer(...)
+                // This is done later in SX126x::write_bytes
+
+                // 8. Define the modulation parameter according to the chosen protocol with the command SetModulationParams(...) 1
+                self.apply_mod_params(spi, delay, conf.mod_params)$(.$await)??;
+
+                // 9. Define the frame format to be used with the command SetPacketParams(...) 2
+                if let Some(packet_params) = conf.packet_params {
+                    self.apply_packet_params(spi, delay, packet_params)$(.$await)??;
+                }
+
+                // 10. Configure DIO and IRQ: use the command SetDioIrqParams(...) to select TxDone IRQ and map this IRQ to a DIO (DIO1,
+                // DIO2 or DIO3)
+                self.apply_dio_irq_params(
+                    spi,
+                    delay,
+                    conf.dio1_irq_mask,
+                    conf.dio1_irq_mask,
+                    conf.dio2_irq_mask,
+                    conf.dio3_irq_mask,
+                )
+                $(.$await)??;
+                let command = Command::SetDio2AsRfSwitchCtrl { enable: true };
+                self.transfer(spi, delay, command, &mut [])$(.$await)??;
+
+                // 11. Define Sync Word value: use the command WriteReg(...) to write the value of the register via direct register access
+                self.apply_sync_word(spi, delay, conf.sync_word)$(.$await)?
+
+                // The rest of the steps are done by the user
+            }
+
+            /// Put the modem in sleep mode. The modem is woken up automatically on the
+            /// next command. If `sleep_config.warm_start` is false, the configuration
+            /// passed to SX126x::init is re-applied after waking up, updated with any
+            /// parameters that were changed since. Note that registers written
+            /// directly are not restored.
+            pub $($async)? fn set_sleep(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                sleep_config: SleepConfig,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let command = Command::SetSleep {
+                    sleep_config: sleep_config.into(),
+                };
+                self.execute(spi, delay, command, &mut [])$(.$await)??;
+                self.state.sleep_config = Some(sleep_config);
+                // 13.1.1: The modem is in sleep mode 500 μs after the rising edge of nss
+                delay.delay_us(500)$(.$await)?;
+                Ok(())
+            }
+
+            /// Set the LoRa Sync word
+            /// Use 0x3444 for public networks like TTN
+            /// Use 0x1424 for private networks
+            pub $($async)? fn set_sync_word(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                sync_word: u16,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.wake_up(spi, delay)$(.$await)??;
+                self.apply_sync_word(spi, delay, sync_word)$(.$await)?
+            }
+
+            $($async)? fn apply_sync_word(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                sync_word: u16,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.state.update_conf(|conf| conf.sync_word = sync_word);
+                let command = Command::WriteRegister {
+                    addr: Register::LoRaSyncWordMsb as u16,
+                    data: &sync_word.to_be_bytes(),
+                };
+                self.transfer(spi, delay, command, &mut [])$(.$await)?
+            }
+
+            /// Set the GFSK sync word. Up to 8 bytes can be written, the number of bits
+            /// actually used is configured with the sync word length in the GFSK packet params
+            pub $($async)? fn set_gfsk_sync_word(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                sync_word: &[u8],
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                debug_assert!(sync_word.len() <= 8);
+                self.write_register(spi, delay, Register::SyncWord0, sync_word)$(.$await)?
+            }
+
+            /// Set the node address used for address filtering in GFSK mode
+            pub $($async)? fn set_node_address(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                address: u8,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.write_register(spi, delay, Register::NodeAddress, &[address])$(.$await)?
+            }
+
+            /// Set the broadcast address used for address filtering in GFSK mode
+            pub $($async)? fn set_broadcast_address(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                address: u8,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.write_register(spi, delay, Register::BroadcastAddress, &[address])
+                    $(.$await)?
+            }
+
+            /// Set the initial value of the CRC in GFSK mode
+            pub $($async)? fn set_crc_seed(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                seed: u16,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.write_register(
+                    spi,
+                    delay,
+                    Register::CrcMsbInitialValue,
+                    &seed.to_be_bytes(),
+                )
+                $(.$await)?
+            }
+
+            /// Set the polynomial used to compute the CRC in GFSK mode
+            pub $($async)? fn set_crc_polynomial(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                polynomial: u16,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.write_register(
+                    spi,
+                    delay,
+                    Register::CrcMsbPolynomialValue,
+                    &polynomial.to_be_bytes(),
+                )
+                $(.$await)?
+            }
+
+            /// Set the 9-bit initial value of the whitening LFSR in GFSK mode.
+            /// The 7 MSB of the WhiteningInitialValueMsb register are left untouched
+            pub $($async)? fn set_whitening_seed(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                seed: u16,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let mut msb = [NOP];
+                self.read_register(spi, delay, Register::WhiteningInitialValueMsb, &mut msb)
+                    $(.$await)??;
+                let seed = seed.to_be_bytes();
+                let msb = (msb[0] & 0xFE) | (seed[0] & 0x01);
+                self.write_register(
+                    spi,
+                    delay,
+                    Register::WhiteningInitialValueMsb,
+                    &[msb, seed[1]],
+                )
+                $(.$await)?
+            }
+
+            /// Set the modem packet type, which can be either GFSK of LoRa
+            pub $($async)? fn set_packet_type(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                packet_type: PacketType,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.wake_up(spi, delay)$(.$await)??;
+                self.apply_packet_type(spi, delay, packet_type)$(.$await)?
+            }
+
+            $($async)? fn apply_packet_type(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                packet_type: PacketType,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.state.errata.set_packet_type(packet_type);
+                self.state.packet_type = packet_type;
+                self.state.update_conf(|conf| conf.packet_type = packet_type);
+                let packet_type = packet_type as u8;
+                self.transfer(spi, delay, Command::SetPacketType { packet_type }, &mut [])
+                    $(.$await)?
+            }
+
+            /// Put the modem in standby mode
+            pub $($async)? fn set_standby(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                standby_config: StandbyConfig,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let standby_config = standby_config as u8;
+                self.execute(spi, delay, Command::SetStandby { standby_config }, &mut [])
+                    $(.$await)?
+            }
+
+            /// Get the current status of the modem
+            pub $($async)? fn get_status(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<Status, SxError<TSPIERR, TPINERR>> {
+                let mut result = [NOP];
+                self.execute(spi, delay, Command::GetStatus, &mut result)$(.$await)??;
+
+                Ok(result[0].into())
+            }
+
+            /// Calibrate the image rejection for a frequency band.
+            /// Use CalibImageFreq::from_range to calibrate an arbitrary band
+            pub $($async)? fn calibrate_image(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                freq: CalibImageFreq,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.wake_up(spi, delay)$(.$await)??;
+                self.apply_calibrate_image(spi, delay, freq)$(.$await)?
+            }
+
+            $($async)? fn apply_calibrate_image(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                freq: CalibImageFreq,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.state.calib_image_freq = Some(freq);
+                let command = Command::CalibrateImage {
+                    freq1: freq.freq1(),
+                    freq2: freq.freq2(),
+                };
+                self.transfer(spi, delay, command, &mut [])$(.$await)?
+            }
+
+            /// Calibrate modem
+            pub $($async)? fn calibrate(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                calib_param: CalibParam,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let calib_param = calib_param.into();
+                self.execute(spi, delay, Command::Calibrate { calib_param }, &mut [])
+                    $(.$await)?
+            }
+
+            /// Write data into a register
+            pub $($async)? fn write_register(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                register: Register,
+                data: &[u8],
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let addr = register as u16;
+                self.execute(spi, delay, Command::WriteRegister { addr, data }, &mut [])
+                    $(.$await)?
+            }
+
+            /// Read data from a register
+            pub $($async)? fn read_register(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                register: Register,
+                result: &mut [u8],
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                debug_assert!(!result.is_empty());
+                let addr = register as u16;
+                self.execute(spi, delay, Command::ReadRegister { addr }, result)$(.$await)?
+            }
+
+            /// Read the typed value of a register
+            pub $($async)? fn read_reg<R: RegisterValue>(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<R, SxError<TSPIERR, TPINERR>> {
+                let mut value = [NOP];
+                self.read_register(spi, delay, R::REGISTER, &mut value)$(.$await)??;
+                Ok(value[0].into())
+            }
+
+            /// Write the typed value of a register
+            pub $($async)? fn write_reg<R: RegisterValue>(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                value: R,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.write_register(spi, delay, R::REGISTER, &[value.into()])$(.$await)?
+            }
+
+            /// Read the typed value of a register, update it using `f` and write it back.
+            /// Returns the written value
+            pub $($async)? fn modify_reg<R: RegisterValue>(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                f: impl FnOnce(R) -> R,
+            ) -> Result<R, SxError<TSPIERR, TPINERR>> {
+                self.wake_up(spi, delay)$(.$await)??;
+                self.update_reg(spi, delay, f)$(.$await)?
+            }
+
+            /// Like modify_reg, without waking up the modem
+            $($async)? fn update_reg<R: RegisterValue>(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                f: impl FnOnce(R) -> R,
+            ) -> Result<R, SxError<TSPIERR, TPINERR>> {
+                let addr = R::REGISTER as u16;
+                let mut value = [NOP];
+                self.transfer(spi, delay, Command::ReadRegister { addr }, &mut value)
+                    $(.$await)??;
+                let value = f(value[0].into());
+                let data = &[value.into()];
+                self.transfer(spi, delay, Command::WriteRegister { addr, data }, &mut [])
+                    $(.$await)??;
+                Ok(value)
+            }
+
+            /// Write data into the buffer at the defined offset
+            pub $($async)? fn write_buffer(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                offset: u8,
+                data: &[u8],
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.execute(spi, delay, Command::WriteBuffer { offset, data }, &mut [])
+                    $(.$await)?
+            }
+
+            /// Read data from the data from the defined offset
+            pub $($async)? fn read_buffer(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                offset: u8,
+                result: &mut [u8],
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.execute(spi, delay, Command::ReadBuffer { offset }, result)$(.$await)?
+            }
+
+            /// Configure the dio2 pin as RF control switch
+            pub $($async)? fn set_dio2_as_rf_switch_ctrl(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                enable: bool,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let command = Command::SetDio2AsRfSwitchCtrl { enable };
+                self.execute(spi, delay, command, &mut [])$(.$await)?
+            }
+
+            /// Select the regulator used by the modem
+            pub $($async)? fn set_regulator_mode(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                regulator_mode: RegulatorMode,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let mode = regulator_mode as u8;
+                self.execute(spi, delay, Command::SetRegulatorMode { mode }, &mut [])
+                    $(.$await)?
+            }
+
+            /// Configure the dio3 pin as TCXO control switch
+            pub $($async)? fn set_dio3_as_tcxo_ctrl(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                tcxo_voltage: TcxoVoltage,
+                tcxo_delay: TcxoDelay,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let command = Command::SetDio3AsTcxoCtrl {
+                    tcxo_voltage: tcxo_voltage as u8,
+                    tcxo_delay: tcxo_delay.into(),
+                };
+                self.execute(spi, delay, command, &mut [])$(.$await)?
+            }
+
+            /// Clear device error register
+            pub $($async)? fn clear_device_errors(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.execute(spi, delay, Command::ClearDeviceErrors, &mut [])$(.$await)?
+            }
+
+            /// Get current device errors
+            pub $($async)? fn get_device_errors(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<DeviceErrors, SxError<TSPIERR, TPINERR>> {
+                let mut result = [NOP; 2];
+                self.execute(spi, delay, Command::GetDeviceErrors, &mut result)$(.$await)??;
+                Ok(DeviceErrors::from(u16::from_le_bytes(result)))
+            }
+
+            /// Enable antenna
+            pub fn set_ant_enabled(&mut self, enabled: bool) -> Result<(), TPINERR> {
+                if enabled {
+                    self.ant_pin.set_high()
+                } else {
+                    self.ant_pin.set_low()
+                }
+            }
+
+            /// Configure IRQ
+            pub $($async)? fn set_dio_irq_params(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                irq_mask: IrqMask,
+                dio1_mask: IrqMask,
+                dio2_mask: IrqMask,
+                dio3_mask: IrqMask,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.wake_up(spi, delay)$(.$await)??;
+                self.apply_dio_irq_params(spi, delay, irq_mask, dio1_mask, dio2_mask, dio3_mask)
+                    $(.$await)?
+            }
+
+            $($async)? fn apply_dio_irq_params(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                irq_mask: IrqMask,
+                dio1_mask: IrqMask,
+                dio2_mask: IrqMask,
+                dio3_mask: IrqMask,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.state.update_conf(|conf| {
+                    conf.dio1_irq_mask = dio1_mask;
+                    conf.dio2_irq_mask = dio2_mask;
+                    conf.dio3_irq_mask = dio3_mask;
+                });
+                let command = Command::SetDioIrqParams {
+                    irq_mask: irq_mask.into(),
+                    dio1_mask: dio1_mask.into(),
+                    dio2_mask: dio2_mask.into(),
+                    dio3_mask: dio3_mask.into(),
+                };
+                self.transfer(spi, delay, command, &mut [])$(.$await)?
+            }
+
+            /// Get the current IRQ status
+            pub $($async)? fn get_irq_status(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<IrqStatus, SxError<TSPIERR, TPINERR>> {
+                let mut status = [NOP, NOP];
+                // 13.3.4: the IRQ status is preceded by the modem status byte
+                self.execute(spi, delay, Command::GetIrqStatus, &mut status)$(.$await)??;
+                Ok(u16::from_be_bytes(status).into())
+            }
+
+            /// Clear the IRQ status
+            pub $($async)? fn clear_irq_status(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                mask: IrqMask,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let mask = mask.into();
+                self.execute(spi, delay, Command::ClearIrqStatus { mask }, &mut [])$(.$await)?
+            }
+
+            /// Get the current IRQ status and clear the flags that were read, so that
+            /// IRQs raised in the meantime are not lost. Returns the most relevant event.
+            pub $($async)? fn poll_event(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<Option<RadioEvent>, SxError<TSPIERR, TPINERR>> {
+                let irq_status = self.get_irq_status(spi, delay)$(.$await)??;
+                self.clear_irq_status(spi, delay, irq_status.into())$(.$await)??;
+                if irq_status.timeout() {
+                    self.stop_rtc_after_timeout(spi, delay)$(.$await)??;
+                }
+                Ok(irq_status.event())
+            }
+
+            /// Put the device in TX mode. It will start sending the data written in the buffer,
+            /// starting at the configured offset
+            pub $($async)? fn set_tx(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                timeout: RxTxTimeout,
+            ) -> Result<Status, SxError<TSPIERR, TPINERR>> {
+                let timeout = timeout.into();
+                self.execute_with_status(spi, delay, Command::SetTx { timeout })$(.$await)?
+            }
+
+            pub $($async)? fn set_rx(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                timeout: RxTxTimeout,
+            ) -> Result<Status, SxError<TSPIERR, TPINERR>> {
+                let timeout = timeout.into();
+                self.execute_with_status(spi, delay, Command::SetRx { timeout })$(.$await)?
+            }
+
+            /// Put the device in sniff mode. The device periodically listens for
+            /// `rx_period` and sleeps for `sleep_period` in between, until a packet
+            /// is detected. Use LoraModParams::rx_duty_cycle_periods to calculate periods
+            /// that fit the preamble length of the transmitter
+            pub $($async)? fn set_rx_duty_cycle(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                rx_period: RxTxTimeout,
+                sleep_period: RxTxTimeout,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let command = Command::SetRxDutyCycle {
+                    rx_period: rx_period.into(),
+                    sleep_period: sleep_period.into(),
+                };
+                self.execute(spi, delay, command, &mut [])$(.$await)?
+            }
+
+            /// Set packet parameters
+            pub $($async)? fn set_packet_params(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                params: PacketParams,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.wake_up(spi, delay)$(.$await)??;
+                self.apply_packet_params(spi, delay, params)$(.$await)?
+            }
+
+            $($async)? fn apply_packet_params(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                params: PacketParams,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.state.update_conf(|conf| conf.packet_params = Some(params));
+                let params: [u8; 9] = params.into();
+                self.state.errata.set_packet_params(params);
+                let command = Command::SetPacketParams { params: &params };
+                self.transfer(spi, delay, command, &mut [])$(.$await)??;
+                if let Some(inverted_iq) = self.state.errata.inverted_iq() {
+                    self.update_reg(spi, delay, |r: IqPolaritySetup| {
+                        r.set_inverted_iq(inverted_iq)
+                    })
+                    $(.$await)??;
+                }
+                Ok(())
+            }
+
+            /// Set modulation parameters.
+            /// LoRa modulations not supported by the variant passed to init are rejected
+            pub $($async)? fn set_mod_params(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                params: ModParams,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let packet_type = self.state.packet_type;
+                self.state
+                    .check(|variant| variant.check_mod_params(packet_type, params))?;
+                self.wake_up(spi, delay)$(.$await)??;
+                self.apply_mod_params(spi, delay, params)$(.$await)?
+            }
+
+            $($async)? fn apply_mod_params(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                params: ModParams,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.state.update_conf(|conf| conf.mod_params = params);
+                let params: [u8; 8] = params.into();
+                self.state.errata.set_mod_params(params);
+                let command = Command::SetModulationParams { params: &params };
+                self.transfer(spi, delay, command, &mut [])$(.$await)??;
+                if let Some(lora_bw500) = self.state.errata.lora_bw500() {
+                    self.update_reg(spi, delay, |r: TxModulation| r.set_lora_bw500(lora_bw500))
+                        $(.$await)??;
+                }
+                Ok(())
+            }
+
+            /// Set TX parameters.
+            /// Fails if the power is out of the range of the variant passed to init
+            pub $($async)? fn set_tx_params(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                params: TxParams,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.state.check(|variant| variant.check_tx_params(params))?;
+                self.wake_up(spi, delay)$(.$await)??;
+                self.apply_tx_params(spi, delay, params)$(.$await)?
+            }
+
+            $($async)? fn apply_tx_params(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                params: TxParams,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.state.update_conf(|conf| conf.tx_params = params);
+                let [power, ramp_time] = <[u8; 2]>::from(params);
+                let command = Command::SetTxParams {
+                    power: power as i8,
+                    ramp_time,
+                };
+                self.transfer(spi, delay, command, &mut [])$(.$await)?
+            }
+
+            /// Set RF frequency.
+            /// Fails if the frequency is out of the range of the variant passed to init.
+            /// If the frequency is out of the band of the last image calibration,
+            /// the image is calibrated for the band of the new frequency first.
+            /// As calibrating requires the modem to be in STDBY_RC mode, the modem
+            /// is then left in STDBY_RC mode
+            pub $($async)? fn set_rf_frequency(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                rf_frequency: Frequency,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.state
+                    .check(|variant| variant.check_rf_frequency(rf_frequency))?;
+                self.wake_up(spi, delay)$(.$await)??;
+                self.apply_rf_frequency(spi, delay, rf_frequency)$(.$await)?
+            }
+
+            $($async)? fn apply_rf_frequency(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                rf_frequency: Frequency,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.state.update_conf(|conf| conf.rf_frequency = rf_frequency);
+                if let Some(calib_image_freq) = self.state.calib_image_freq {
+                    if !calib_image_freq.contains(rf_frequency.hz()) {
+                        // 13.1.13: The calibration must be launched in STDBY_RC mode
+                        let standby_config = StandbyConfig::StbyRc as u8;
+                        let command = Command::SetStandby { standby_config };
+                        self.transfer(spi, delay, command, &mut [])$(.$await)??;
+                        let freq = CalibImageFreq::from_rf_frequency(rf_frequency.hz());
+                        self.apply_calibrate_image(spi, delay, freq)$(.$await)??;
+                    }
+                }
+                let rf_freq = rf_frequency.rf_freq();
+                self.transfer(spi, delay, Command::SetRfFrequency { rf_freq }, &mut [])
+                    $(.$await)?
+            }
+
+            /// Set Power Amplifier configuration.
+            /// Fails if the selected PA is not available on the variant passed to init
+            pub $($async)? fn set_pa_config(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                pa_config: PaConfig,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.state.check(|variant| variant.check_pa_config(pa_config))?;
+                self.wake_up(spi, delay)$(.$await)??;
+                // 13.1.14: SetPaConfig resets the over current protection level
+                self.state.ocp = None;
+                self.apply_pa_config(spi, delay, pa_config)$(.$await)?
+            }
+
+            $($async)? fn apply_pa_config(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                pa_config: PaConfig,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.state.update_conf(|conf| conf.pa_config = pa_config);
+                self.state.errata.set_pa_config(pa_config);
+                let pa_config = pa_config.into();
+                self.transfer(spi, delay, Command::SetPaConfig { pa_config }, &mut [])
+                    $(.$await)??;
+                // The register is reset to its default after a cold start,
+                // and is restored along with the PA config
+                if self.state.errata.tx_clamp() {
+                    self.update_reg(spi, delay, |r: TxClampConfig| r.set_threshold(0x0F))
+                        $(.$await)??;
+                }
+                if let Some(ocp) = self.state.ocp {
+                    let addr = OcpConfiguration::REGISTER as u16;
+                    let data = &[ocp.into()];
+                    self.transfer(spi, delay, Command::WriteRegister { addr, data }, &mut [])
+                        $(.$await)??;
+                }
+                Ok(())
+            }
+
+            /// Configure the PA and output power, and set the over current protection level to match.
+            /// The level is restored after waking up from a cold start, until the PA config is set again.
+            /// Use OutputPower::pa_settings to choose the settings for a target output power
+            pub $($async)? fn set_output_power(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                settings: PaSettings,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.state
+                    .check(|variant| variant.check_pa_config(settings.pa_config))?;
+                self.wake_up(spi, delay)$(.$await)??;
+                // 13.1.14: SetPaConfig resets the over current protection level,
+                // apply_pa_config writes it afterwards
+                self.state.ocp = Some(settings.ocp);
+                self.apply_pa_config(spi, delay, settings.pa_config)$(.$await)??;
+                self.set_tx_params(spi, delay, settings.tx_params)$(.$await)?
+            }
+
+            /// Configure the base addresses in the buffer
+            pub $($async)? fn set_buffer_base_address(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                tx_base_addr: u8,
+                rx_base_addr: u8,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let command = Command::SetBufferBaseAddress {
+                    tx_base_addr,
+                    rx_base_addr,
+                };
+                self.execute(spi, delay, command, &mut [])$(.$await)?
+            }
+
+            /// High level method to send a message. This methods writes the data in the buffer,
+            /// puts the device in TX mode, and waits until the devices
+            /// is done sending the data or a timeout occurs.
+            /// Please note that this method updates the packet params
+            #[allow(clippy::too_many_arguments)]
+            pub $($async)? fn write_bytes<TDIO1: $pin<Error = TPINERR>>(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                data: &[u8],
+                timeout: RxTxTimeout,
+                preamble_len: u16,
+                crc_type: packet::lora::LoRaCrcType,
+                dio1_pin: &mut TDIO1,
+            ) -> Result<Status, SxError<TSPIERR, TPINERR>> {
+                use packet::lora::LoRaPacketParams;
+                // Write data to buffer
+                self.write_buffer(spi, delay, 0x00, data)$(.$await)??;
+
+                // Set packet params
+                let params = LoRaPacketParams::default()
+                    .set_preamble_len(preamble_len)
+                    .set_payload_len(data.len() as u8)
+                    .set_crc_type(crc_type)
+                    .into();
+
+                self.set_packet_params(spi, delay, params)$(.$await)??;
+
+                // Set tx mode
+                let status = self.set_tx(spi, delay, timeout)$(.$await)??;
+                // Wait for busy line to go low
+                self.wait_on_busy(delay)$(.$await)??;
+                // Wait on dio1 going high
+                let timeout_us = self.state.dio1_timeout.timeout_us(Some(timeout));
+                self.wait_on_dio1(delay, dio1_pin, timeout_us)$(.$await)??;
+                // Clear IRQ
+                self.clear_irq_status(spi, delay, IrqMask::all())$(.$await)??;
+                // Write completed!
+                Ok(status)
+            }
+
+            /// Set the parameters used for channel activity detection
+            pub $($async)? fn set_cad_params(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                params: CadParams,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let params = params.into();
+                self.execute(spi, delay, Command::SetCadParams { params }, &mut [])$(.$await)?
+            }
+
+            /// Start channel activity detection. Only available in LoRa mode.
+            /// The modem raises the CadDone IRQ when done, and CadDetected if
+            /// activity was detected
+            pub $($async)? fn set_cad(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.execute(spi, delay, Command::SetCad, &mut [])$(.$await)?
+            }
+
+            /// High level method to check whether the channel is free, for use in
+            /// listen-before-talk schemes. This methods starts channel activity detection
+            /// using the configured CAD params and waits until it is done.
+            /// Please note that the CadDone IRQ must be mapped to dio1
+            pub $($async)? fn channel_is_free<TDIO1: $pin<Error = TPINERR>>(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                dio1_pin: &mut TDIO1,
+            ) -> Result<bool, SxError<TSPIERR, TPINERR>> {
+                self.set_cad(spi, delay)$(.$await)??;
+                // Wait for busy line to go low
+                self.wait_on_busy(delay)$(.$await)??;
+                // Wait on dio1 going high
+                let timeout_us = self.state.dio1_timeout.timeout_us(None);
+                self.wait_on_dio1(delay, dio1_pin, timeout_us)$(.$await)??;
+                let irq_status = self.get_irq_status(spi, delay)$(.$await)??;
+                // Clear IRQ
+                self.clear_irq_status(spi, delay, IrqMask::all())$(.$await)??;
+                Ok(!irq_status.cad_detected())
+            }
+
+            /// High level method to receive a message. This method puts the device in
+            /// RX mode, waits until a packet is received or a timeout occurs, and reads
+            /// the received data into `buf`. If the packet does not fit, it is truncated.
+            /// Please note that the RxDone, Timeout, CrcErr and HeaderError IRQs must be
+            /// mapped to dio1. Other IRQs mapped to dio1, like PreambleDetected, are cleared
+            /// while waiting for the packet
+            pub $($async)? fn read_bytes<TDIO1: $pin<Error = TPINERR>>(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                buf: &mut [u8],
+                timeout: RxTxTimeout,
+                dio1_pin: &mut TDIO1,
+            ) -> Result<RxPacket, SxError<TSPIERR, TPINERR>> {
+                // Set rx mode
+                self.set_rx(spi, delay, timeout)$(.$await)??;
+                // Wait for busy line to go low
+                self.wait_on_busy(delay)$(.$await)??;
+                let timeout_us = self.state.dio1_timeout.timeout_us(Some(timeout));
+                let irq_status = loop {
+                    // Wait on dio1 going high
+                    self.wait_on_dio1(delay, dio1_pin, timeout_us)$(.$await)??;
+                    let irq_status = self.get_irq_status(spi, delay)$(.$await)??;
+                    if irq_status.rx_done() || irq_status.timeout() || irq_status.header_error() {
+                        // Clear IRQ
+                        self.clear_irq_status(spi, delay, IrqMask::all())$(.$await)??;
+                        break irq_status;
+                    }
+                    // Dio1 went high before the packet was received. Clear the IRQs
+                    // read so far, so that it goes low again
+                    self.clear_irq_status(spi, delay, irq_status.into())$(.$await)??;
+                };
+
+                if irq_status.timeout() {
+                    self.stop_rtc_after_timeout(spi, delay)$(.$await)??;
+                    return Err(RxError::Timeout.into());
+                }
+                if irq_status.header_error() {
+                    return Err(RxError::HeaderError.into());
+                }
+                if irq_status.crc_err() {
+                    return Err(RxError::CrcError.into());
+                }
+
+                // Get payload length and start offset in rx buffer
+                let buffer_status = self.get_rx_buffer_status(spi, delay)$(.$await)??;
+                let len = buffer_status
+                    .payload_length_rx()
+                    .min(buf.len().min(u8::MAX as usize) as u8);
+                self.read_buffer(
+                    spi,
+                    delay,
+                    buffer_status.rx_start_buffer_pointer(),
+                    &mut buf[..len as usize],
+                )
+                $(.$await)??;
+
+                let packet_status = self.get_packet_status(spi, delay)$(.$await)??;
+                // Read completed!
+                Ok(RxPacket::new(len, packet_status))
+            }
+
+            /// Get Rx buffer status, containing the length of the last received packet
+            /// and the address of the first byte received.
+            pub $($async)? fn get_rx_buffer_status(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<RxBufferStatus, SxError<TSPIERR, TPINERR>> {
+                let mut result = [NOP, NOP];
+                self.execute(spi, delay, Command::GetRxBufferStatus, &mut result)$(.$await)??;
+
+                Ok(result.into())
+            }
+
+            /// Get the packet status of the last received packet, containing the
+            /// RSSI and SNR in LoRa mode, or the RX status and RSSI in GFSK mode.
+            /// It is decoded according to the current packet type
+            pub $($async)? fn get_packet_status(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<PacketStatus, SxError<TSPIERR, TPINERR>> {
+                let mut result = [NOP; 3];
+                self.execute(spi, delay, Command::GetPacketStatus, &mut result)$(.$await)??;
+
+                Ok(PacketStatus::new(self.state.packet_type, result))
+            }
+
+            /// Get the instantaneous RSSI in dBm, measured while in RX mode.
+            /// It has a resolution of 0.5 dBm
+            pub $($async)? fn get_rssi_inst(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<f32, SxError<TSPIERR, TPINERR>> {
+                let mut result = [NOP];
+                self.execute(spi, delay, Command::GetRssiInst, &mut result)$(.$await)??;
+
+                // 13.5.2: RssiInst = -RssiInst / 2 dBm
+                Ok(-(result[0] as f32) / 2.)
+            }
+
+            /// Get the statistics on the received packets since the last call
+            /// to SX126x::reset_stats
+            pub $($async)? fn get_stats(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<RxStats, SxError<TSPIERR, TPINERR>> {
+                let mut result = [NOP; 6];
+                self.execute(spi, delay, Command::GetStats, &mut result)$(.$await)??;
+
+                Ok(result.into())
+            }
+
+            /// Reset the statistics on the received packets
+            pub $($async)? fn reset_stats(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.execute(spi, delay, Command::ResetStats, &mut [])$(.$await)?
+            }
+
+            /// Execute any command, including commands without a dedicated method.
+            /// The response of the modem is read into `response`, which should be
+            /// `command.response_len()` bytes long, or as long as the data to read
+            /// for ReadRegister and ReadBuffer. Note that the state kept by the driver
+            /// is not updated, use the dedicated methods for commands like SetSleep
+            /// or to change parameters that should be restored after a cold start
+            pub $($async)? fn execute(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                command: Command<'_>,
+                response: &mut [u8],
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                self.wake_up(spi, delay)$(.$await)??;
+                self.transfer(spi, delay, command, response)$(.$await)?
+            }
+
+            /// Waits until the modem is ready, then sends the command and reads
+            /// its response within a single transaction. Does not wake up the modem
+            $($async)? fn transfer(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                command: Command<'_>,
+                response: &mut [u8],
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let args = command.args();
+                // NOP bytes sent between the arguments and the response, such as for the status byte
+                let padding = [NOP];
+                let padding_len = if response.is_empty() {
+                    0
+                } else {
+                    command.response_offset() - 1 - args.len()
+                };
+                let to_spi_error = if response.is_empty() {
+                    SpiError::Write
+                } else {
+                    SpiError::Transfer
+                };
+                self.wait_on_busy(delay)$(.$await)??;
+                spi.transaction(&mut [
+                    Operation::Write(&[command.opcode()]),
+                    Operation::Write(args.head()),
+                    Operation::Write(args.data()),
+                    Operation::Write(&padding[..padding_len]),
+                    Operation::Read(response),
+                ])
+                $(.$await)?
+                .map_err(to_spi_error)?;
+                Ok(())
+            }
+
+            /// Executes a command with arguments and returns the status
+            /// the modem clocks out while receiving the first argument byte
+            $($async)? fn execute_with_status(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+                command: Command<'_>,
+            ) -> Result<Status, SxError<TSPIERR, TPINERR>> {
+                let args = command.args();
+                debug_assert!(!args.head().is_empty());
+                let (first, rest) = args.head().split_at(1);
+                let mut status = [NOP];
+                self.wake_up(spi, delay)$(.$await)??;
+                self.wait_on_busy(delay)$(.$await)??;
+                spi.transaction(&mut [
+                    Operation::Write(&[command.opcode()]),
+                    Operation::Transfer(&mut status, first),
+                    Operation::Write(rest),
+                    Operation::Write(args.data()),
+                ])
+                $(.$await)?
+                .map_err(SpiError::Transfer)?;
+                Ok(status[0].into())
+            }
+
+            /// Wakes up the modem by sending GetStatus, and waits until it is
+            /// ready to accept commands. After a cold start,
+            /// the last known configuration is re-applied.
+            $($async)? fn wake_up(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                let sleep_config = match self.state.sleep_config.take() {
+                    Some(sleep_config) => sleep_config,
+                    None => return Ok(()),
+                };
+                // 13.1.1: A falling edge on nss wakes the modem up. Not every SPI device
+                // toggles nss for an empty transaction, so a GetStatus command is sent
+                // without waiting on the busy pin, which stays high while asleep
+                let mut status = [NOP];
+                spi.transaction(&mut [
+                    Operation::Write(&[Command::GetStatus.opcode()]),
+                    Operation::Read(&mut status),
+                ])
+                $(.$await)?
+                .map_err(SpiError::Transfer)?;
+                self.wait_on_busy(delay)$(.$await)??;
+
+                if !sleep_config.warm_start {
+                    if let Some(conf) = self.state.conf {
+                        self.configure(spi, delay, &conf)$(.$await)??;
+                    }
+                }
+                Ok(())
+            }
+
+            /// 15.3: Stop the RTC and clear the timeout event after an RX timeout,
+            /// if the workaround applies
+            $($async)? fn stop_rtc_after_timeout(
+                &mut self,
+                spi: &mut TSPI,
+                delay: &mut impl DelayNs,
+            ) -> Result<(), SxError<TSPIERR, TPINERR>> {
+                if self.state.errata.stop_rtc() {
+                    self.write_reg(spi, delay, RtcControl::STOPPED)$(.$await)??;
+                    self.modify_reg(spi, delay, |r: EventMask| r.clear_timeout_event())
+                        $(.$await)??;
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dio1_timeout() {
+        let auto = |steps: u32| Dio1Timeout::Auto.timeout_us(Some(steps.into()));
+        // 1 s, plus 62.5 ms and 10 ms
+        assert_eq!(auto(64_000), Some(1_072_500));
+        assert_eq!(auto(1), Some(10_015));
+        // Largest timeout, about 262 s
+        assert_eq!(auto(0xFF_FFFE), Some(278_537_966));
+        // Single mode without timeout, and continuous RX
+        assert_eq!(auto(0), Some(10_000_000));
+        assert_eq!(auto(0xFF_FFFF), Some(10_000_000));
+        assert_eq!(Dio1Timeout::Auto.timeout_us(None), Some(10_000_000));
+
+        let timeout = Some(RxTxTimeout::from_ms(1000));
+        assert_eq!(Dio1Timeout::Us(500).timeout_us(timeout), Some(500));
+        assert_eq!(Dio1Timeout::Never.timeout_us(timeout), None);
+    }
+}
@@ -1,5 +1,7 @@
 use core::fmt::{self, Debug};
 
+use crate::conf::ConfigError;
+
 pub enum SpiError<TSPIERR> {
     Write(TSPIERR),
     Transfer(TSPIERR),
@@ -28,9 +30,36 @@ impl<TPINERR: Debug> Debug for PinError<TPINERR> {
     }
 }
 
+/// Reasons for a failed reception
+#[derive(Copy, Clone, Debug)]
+pub enum RxError {
+    /// No packet was received before the timeout elapsed
+    Timeout,
+    /// A packet was received, but its payload CRC was invalid
+    CrcError,
+    /// A LoRa header was received, but its CRC was invalid
+    HeaderError,
+}
+
+/// Pins the driver waits on
+#[derive(Copy, Clone, Debug)]
+pub enum WaitFor {
+    /// Waiting for the busy pin to go low
+    Busy,
+    /// Waiting for the dio1 pin to go high
+    Dio1,
+}
+
 pub enum SxError<TSPIERR, TPINERR> {
     Spi(SpiError<TSPIERR>),
     Pin(PinError<TPINERR>),
+    Rx(RxError),
+    /// A pin did not reach the expected level in time
+    Timeout {
+        waiting_for: WaitFor,
+    },
+    /// The configuration is not supported by its chip variant
+    Config(ConfigError),
 }
 
 impl<TSPIERR: Debug, TPINERR: Debug> Debug for SxError<TSPIERR, TPINERR> {
@@ -38,10 +67,27 @@ impl<TSPIERR: Debug, TPINERR: Debug> Debug for SxError<TSPIERR, TPINERR> {
         match self {
             Self::Spi(err) => write!(f, "Spi({:?})", err),
             Self::Pin(err) => write!(f, "Pin({:?})", err),
+            Self::Rx(err) => write!(f, "Rx({:?})", err),
+            Self::Timeout { waiting_for } => {
+                write!(f, "Timeout {{ waiting_for: {:?} }}", waiting_for)
+            }
+            Self::Config(err) => write!(f, "Config({:?})", err),
         }
     }
 }
 
+impl<TSPIERR, TPINERR> From<RxError> for SxError<TSPIERR, TPINERR> {
+    fn from(rx_err: RxError) -> Self {
+        SxError::Rx(rx_err)
+    }
+}
+
+impl<TSPIERR, TPINERR> From<ConfigError> for SxError<TSPIERR, TPINERR> {
+    fn from(config_err: ConfigError) -> Self {
+        SxError::Config(config_err)
+    }
+}
+
 impl<TSPIERR, TPINERR> From<SpiError<TSPIERR>> for SxError<TSPIERR, TPINERR> {
     fn from(spi_err: SpiError<TSPIERR>) -> Self {
         SxError::Spi(spi_err)
@@ -0,0 +1,77 @@
+//! Keeps track of the settings that determine which of the
+//! workarounds from chapter 15 of the datasheet apply
+use crate::op::modulation::lora::LoRaBandWidth;
+use crate::op::packet::lora::{LoRaHeaderType, LoRaInvertIq};
+use crate::op::*;
+
+#[derive(Copy, Clone)]
+pub(crate) struct Errata {
+    /// Whether the workarounds are applied
+    pub enabled: bool,
+    packet_type: Option<PacketType>,
+    mod_params: [u8; 8],
+    packet_params: [u8; 9],
+    device_sel: Option<DeviceSel>,
+}
+
+impl Errata {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            packet_type: None,
+            mod_params: [0; 8],
+            packet_params: [0; 9],
+            device_sel: None,
+        }
+    }
+
+    pub fn set_packet_type(&mut self, packet_type: PacketType) {
+        self.packet_type = Some(packet_type);
+    }
+
+    pub fn set_mod_params(&mut self, params: [u8; 8]) {
+        self.mod_params = params;
+    }
+
+    pub fn set_packet_params(&mut self, params: [u8; 9]) {
+        self.packet_params = params;
+    }
+
+    pub fn set_pa_config(&mut self, pa_config: PaConfig) {
+        self.device_sel = Some(pa_config.device_sel());
+    }
+
+    fn lora(&self) -> bool {
+        self.enabled && self.packet_type == Some(PacketType::LoRa)
+    }
+
+    /// 15.1: Whether the TX modulation should be set up for LoRa using a bandwidth of 500 kHz.
+    /// None if the workaround does not apply
+    pub fn lora_bw500(&self) -> Option<bool> {
+        if !self.enabled || self.packet_type.is_none() {
+            return None;
+        }
+        Some(self.lora() && self.mod_params[1] == LoRaBandWidth::BW500 as u8)
+    }
+
+    /// 15.4: Whether the IQ polarity should be set up for inverted IQ.
+    /// None if the workaround does not apply
+    pub fn inverted_iq(&self) -> Option<bool> {
+        if !self.lora() {
+            return None;
+        }
+        Some(self.packet_params[5] == LoRaInvertIq::Inverted as u8)
+    }
+
+    /// 15.2: Whether the PA clamping threshold should be raised,
+    /// which applies when the high power PA is used
+    pub fn tx_clamp(&self) -> bool {
+        self.enabled && self.device_sel == Some(DeviceSel::SX1262)
+    }
+
+    /// 15.3: Whether the RTC should be stopped after an RX timeout,
+    /// which applies to LoRa packets with an implicit header
+    pub fn stop_rtc(&self) -> bool {
+        self.lora() && self.packet_params[2] == LoRaHeaderType::FixedLen as u8
+    }
+}
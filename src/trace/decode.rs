@@ -0,0 +1,421 @@
+//! Decoding of captured SPI transactions into commands, see chapter 13
+use core::convert::TryFrom;
+use core::fmt::{self, Debug};
+
+use crate::op::cad::CadParams;
+use crate::op::init::SleepConfig;
+use crate::op::modulation::{gfsk::GfskModParams, lora::LoraModParams};
+use crate::op::packet::{gfsk::GfskPacketParams, lora::LoRaPacketParams};
+use crate::op::{hz_from_rf_freq, opcode, Command, PacketType, XTAL_HZ};
+use crate::reg::Register;
+
+/// Error decoding a transaction
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The transaction did not contain any bytes. The driver never sends one,
+    /// but a traced transaction which only contains delays is empty, and so is
+    /// a captured nss pulse used by other software to wake the modem up
+    Empty,
+    /// The opcode does not belong to any known command
+    UnknownOpcode(u8),
+    /// The transaction is too short for the arguments of the command
+    TooShort { opcode: u8 },
+}
+
+/// Command decoded from a transaction, together with the response of the modem
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Transaction<'a> {
+    pub command: Command<'a>,
+    /// Bytes returned on miso after the status byte. Empty for commands
+    /// without response
+    pub response: &'a [u8],
+    /// Packet type set when the command was sent, if known. Modulation and
+    /// packet params are printed by field if it is
+    pub packet_type: Option<PacketType>,
+}
+
+/// Decode a transaction from the bytes sent on mosi and received on miso.
+/// Pass an empty `miso` if it was not captured. Use a [`Decoder`] to
+/// decode the modulation and packet params in a sequence of transactions
+pub fn decode<'a>(mosi: &'a [u8], miso: &'a [u8]) -> Result<Transaction<'a>, DecodeError> {
+    let command = decode_command(mosi)?;
+    let response = miso.get(command.response_offset()..).unwrap_or(&[]);
+    let response = match command.response_len() {
+        Some(len) => &response[..len.min(response.len())],
+        None => response,
+    };
+    Ok(Transaction {
+        command,
+        response,
+        packet_type: None,
+    })
+}
+
+/// Decodes a sequence of transactions, keeping track of the packet type
+/// set using SetPacketType, as the meaning of the modulation and
+/// packet params depends on it
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Decoder {
+    packet_type: Option<PacketType>,
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode the next transaction, see [`decode`]
+    pub fn decode<'a>(
+        &mut self,
+        mosi: &'a [u8],
+        miso: &'a [u8],
+    ) -> Result<Transaction<'a>, DecodeError> {
+        let mut transaction = decode(mosi, miso)?;
+        if let Command::SetPacketType { packet_type } = transaction.command {
+            self.packet_type = PacketType::try_from(packet_type).ok();
+        }
+        transaction.packet_type = self.packet_type;
+        Ok(transaction)
+    }
+}
+
+/// Decode the command sent on mosi
+fn decode_command(mosi: &[u8]) -> Result<Command<'_>, DecodeError> {
+    use self::opcode::*;
+    use Command::*;
+
+    let (&opcode, args) = mosi.split_first().ok_or(DecodeError::Empty)?;
+    let too_short = DecodeError::TooShort { opcode };
+    let arg = |i: usize| args.get(i).copied().ok_or(too_short);
+    let u16_arg = |i: usize| Ok(u16::from_be_bytes([arg(i)?, arg(i + 1)?]));
+    let u24_arg = |i: usize| Ok(u32::from_be_bytes([0, arg(i)?, arg(i + 1)?, arg(i + 2)?]));
+    let array_arg = |len: usize| args.get(..len).ok_or(too_short);
+
+    let command = match opcode {
+        SET_SLEEP => SetSleep {
+            sleep_config: arg(0)?,
+        },
+        SET_STANDBY => SetStandby {
+            standby_config: arg(0)?,
+        },
+        SET_FS => SetFs,
+        SET_TX => SetTx {
+            timeout: u24_arg(0)?,
+        },
+        SET_RX => SetRx {
+            timeout: u24_arg(0)?,
+        },
+        STOP_TIMER_ON_PREAMBLE => StopTimerOnPreamble {
+            enable: arg(0)? != 0,
+        },
+        SET_RX_DUTY_CYCLE => SetRxDutyCycle {
+            rx_period: u24_arg(0)?,
+            sleep_period: u24_arg(3)?,
+        },
+        SET_CAD => SetCad,
+        SET_TX_CONTINUOUS_WAVE => SetTxContinuousWave,
+        SET_TX_INFINITE_PREAMBLE => SetTxInfinitePreamble,
+        SET_REGULATOR_MODE => SetRegulatorMode { mode: arg(0)? },
+        CALIBRATE => Calibrate {
+            calib_param: arg(0)?,
+        },
+        CALIBRATE_IMAGE => CalibrateImage {
+            freq1: arg(0)?,
+            freq2: arg(1)?,
+        },
+        SET_PA_CONFIG => SetPaConfig {
+            pa_config: [arg(0)?, arg(1)?, arg(2)?, arg(3)?],
+        },
+        SET_RX_TX_FALLBACK_MODE => SetRxTxFallbackMode { mode: arg(0)? },
+        WRITE_REGISTER => WriteRegister {
+            addr: u16_arg(0)?,
+            data: &args[2..],
+        },
+        READ_REGISTER => ReadRegister { addr: u16_arg(0)? },
+        WRITE_BUFFER => WriteBuffer {
+            offset: arg(0)?,
+            data: &args[1..],
+        },
+        READ_BUFFER => ReadBuffer { offset: arg(0)? },
+        SET_DIO_IRQ_PARAMS => SetDioIrqParams {
+            irq_mask: u16_arg(0)?,
+            dio1_mask: u16_arg(2)?,
+            dio2_mask: u16_arg(4)?,
+            dio3_mask: u16_arg(6)?,
+        },
+        GET_IRQ_STATUS => GetIrqStatus,
+        CLEAR_IRQ_STATUS => ClearIrqStatus { mask: u16_arg(0)? },
+        SET_DIO2_AS_RF_SWITCH_CTRL => SetDio2AsRfSwitchCtrl {
+            enable: arg(0)? != 0,
+        },
+        SET_DIO3_AS_TCXO_CTRL => SetDio3AsTcxoCtrl {
+            tcxo_voltage: arg(0)?,
+            tcxo_delay: u24_arg(1)?,
+        },
+        SET_RF_FREQUENCY => SetRfFrequency {
+            rf_freq: u32::from_be_bytes([arg(0)?, arg(1)?, arg(2)?, arg(3)?]),
+        },
+        SET_PACKET_TYPE => SetPacketType {
+            packet_type: arg(0)?,
+        },
+        GET_PACKET_TYPE => GetPacketType,
+        SET_TX_PARAMS => SetTxParams {
+            power: arg(0)? as i8,
+            ramp_time: arg(1)?,
+        },
+        SET_MODULATION_PARAMS => SetModulationParams { params: args },
+        SET_PACKET_PARAMS => SetPacketParams { params: args },
+        SET_CAD_PARAMS => {
+            let mut params = [0; 7];
+            params.copy_from_slice(array_arg(7)?);
+            SetCadParams { params }
+        }
+        SET_BUFFER_BASE_ADDRESS => SetBufferBaseAddress {
+            tx_base_addr: arg(0)?,
+            rx_base_addr: arg(1)?,
+        },
+        SET_LORA_SYMB_NUM_TIMEOUT => SetLoRaSymbNumTimeout { symb_num: arg(0)? },
+        GET_STATUS => GetStatus,
+        GET_RSSI_INST => GetRssiInst,
+        GET_RX_BUFFER_STATUS => GetRxBufferStatus,
+        GET_PACKET_STATUS => GetPacketStatus,
+        GET_DEVICE_ERRORS => GetDeviceErrors,
+        CLEAR_DEVICE_ERRORS => ClearDeviceErrors,
+        GET_STATS => GetStats,
+        RESET_STATS => ResetStats,
+        _ => return Err(DecodeError::UnknownOpcode(opcode)),
+    };
+    Ok(command)
+}
+
+/// Formats bytes as hexadecimal
+struct Hex<'a>(&'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, b) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{:02X}", b)?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// Formats params using the `Debug` output of `T`, or as hexadecimal if they are invalid.
+/// Missing trailing params are taken to be zero, as only the params used by the packet
+/// type need to be sent
+fn write_params<T, const N: usize>(f: &mut fmt::Formatter<'_>, params: &[u8]) -> fmt::Result
+where
+    T: TryFrom<[u8; N]> + Debug,
+{
+    let mut raw = [0; N];
+    let len = params.len().min(N);
+    raw[..len].copy_from_slice(&params[..len]);
+    match T::try_from(raw) {
+        Ok(params) => write!(f, "{:?}", params),
+        Err(_) => write!(f, "{}", Hex(params)),
+    }
+}
+
+/// Formats a register address by name, if it is known
+struct RegisterName(u16);
+
+impl fmt::Display for RegisterName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match Register::try_from(self.0) {
+            Ok(register) => write!(f, "{:?}", register),
+            Err(addr) => write!(f, "{:#06X}", addr),
+        }
+    }
+}
+
+impl fmt::Display for Command<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Command::*;
+
+        match *self {
+            SetRfFrequency { rf_freq } => {
+                let hz = hz_from_rf_freq(rf_freq, XTAL_HZ);
+                write!(
+                    f,
+                    "SetRfFrequency {}.{:06} MHz",
+                    hz / 1_000_000,
+                    hz % 1_000_000
+                )
+            }
+            CalibrateImage { freq1, freq2 } => write!(
+                f,
+                "CalibrateImage {}-{} MHz",
+                freq1 as u32 * 4,
+                freq2 as u32 * 4
+            ),
+            SetPacketType { packet_type: 0x00 } => write!(f, "SetPacketType GFSK"),
+            SetPacketType { packet_type: 0x01 } => write!(f, "SetPacketType LoRa"),
+            SetSleep { sleep_config } => match SleepConfig::try_from(sleep_config) {
+                Ok(config) => write!(f, "SetSleep {:?}", config),
+                Err(_) => write!(f, "SetSleep {:#04X}", sleep_config),
+            },
+            SetStandby { standby_config: 0x00 } => write!(f, "SetStandby StbyRc"),
+            SetStandby { standby_config: 0x01 } => write!(f, "SetStandby StbyXosc"),
+            WriteRegister { addr, data } => {
+                write!(f, "WriteRegister {} {}", RegisterName(addr), Hex(data))
+            }
+            ReadRegister { addr } => write!(f, "ReadRegister {}", RegisterName(addr)),
+            WriteBuffer { offset, data } => {
+                write!(f, "WriteBuffer {:#04X} {}", offset, Hex(data))
+            }
+            ReadBuffer { offset } => write!(f, "ReadBuffer {:#04X}", offset),
+            SetModulationParams { params } => write!(f, "SetModulationParams {}", Hex(params)),
+            SetPacketParams { params } => write!(f, "SetPacketParams {}", Hex(params)),
+            SetDioIrqParams {
+                irq_mask,
+                dio1_mask,
+                dio2_mask,
+                dio3_mask,
+            } => write!(
+                f,
+                "SetDioIrqParams {{ irq_mask: {:#06X}, dio1_mask: {:#06X}, dio2_mask: {:#06X}, dio3_mask: {:#06X} }}",
+                irq_mask, dio1_mask, dio2_mask, dio3_mask
+            ),
+            ClearIrqStatus { mask } => write!(f, "ClearIrqStatus {:#06X}", mask),
+            SetCadParams { params } => {
+                write!(f, "SetCadParams ")?;
+                write_params::<CadParams, 7>(f, &params)
+            }
+            ref command => write!(f, "{:?}", command),
+        }
+    }
+}
+
+impl fmt::Display for Transaction<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Command::*;
+        use PacketType::*;
+
+        match (self.command, self.packet_type) {
+            (SetModulationParams { params }, Some(LoRa)) => {
+                write!(f, "SetModulationParams ")?;
+                write_params::<LoraModParams, 8>(f, params)?;
+            }
+            (SetModulationParams { params }, Some(GFSK)) => {
+                write!(f, "SetModulationParams ")?;
+                write_params::<GfskModParams, 8>(f, params)?;
+            }
+            (SetPacketParams { params }, Some(LoRa)) => {
+                write!(f, "SetPacketParams ")?;
+                write_params::<LoRaPacketParams, 9>(f, params)?;
+            }
+            (SetPacketParams { params }, Some(GFSK)) => {
+                write!(f, "SetPacketParams ")?;
+                write_params::<GfskPacketParams, 9>(f, params)?;
+            }
+            (command, _) => write!(f, "{}", command)?,
+        }
+        if !self.response.is_empty() {
+            write!(f, " -> {}", Hex(self.response))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::string::{String, ToString};
+    use std::vec::Vec;
+
+    /// Decode a trace given as mosi and miso bytes per transaction
+    fn decode_trace(trace: &[(&[u8], &[u8])]) -> Vec<String> {
+        let mut decoder = Decoder::new();
+        trace
+            .iter()
+            .map(|(mosi, miso)| decoder.decode(mosi, miso).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn recorded_trace() {
+        // Part of the traffic of init, followed by a switch to GFSK
+        let trace: &[(&[u8], &[u8])] = &[
+            (&[0x8B, 0x07, 0x04, 0x01, 0x00], &[]),
+            (&[0x80, 0x00], &[0xA2, 0xA2]),
+            (&[0x8A, 0x01], &[0xA2, 0xA2]),
+            (&[0x86, 0x36, 0x40, 0x00, 0x00], &[0xA2; 5]),
+            (
+                &[0x8B, 0x07, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00],
+                &[0xA2; 9],
+            ),
+            (
+                &[0x8C, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+                &[0xA2; 10],
+            ),
+            (
+                &[0x1D, 0x07, 0x36, 0x00, 0x00],
+                &[0xA2, 0xA2, 0xA2, 0xA2, 0x0D],
+            ),
+            (&[0x0D, 0x07, 0x40, 0x14, 0x24], &[0xA2; 5]),
+            (&[0x8A, 0x00], &[0xA2, 0xA2]),
+            // Only the LoRa params are sent, which are not valid GFSK params
+            (&[0x8B, 0x07, 0x04, 0x01, 0x00], &[]),
+            (&[0x8B, 0x00, 0x50, 0x00, 0x09, 0x0B, 0x00, 0x66, 0x66], &[]),
+            (
+                &[0x8C, 0x00, 0x20, 0x05, 0x10, 0x00, 0x01, 0xFF, 0x06, 0x00],
+                &[],
+            ),
+        ];
+        let expected = [
+            "SetModulationParams [07 04 01 00]",
+            "SetStandby StbyRc",
+            "SetPacketType LoRa",
+            "SetRfFrequency 868.000000 MHz",
+            "SetModulationParams LoraModParams { spread_factor: SF7, bandwidth: BW125, \
+             coding_rate: CR4_5, low_dr_opt: false }",
+            "SetPacketParams LoRaPacketParams { preamble_len: 8, header_type: VarLen, \
+             payload_len: 0, crc_type: CrcOff, invert_iq: Standard }",
+            "ReadRegister IqPolaritySetup -> [0D]",
+            "WriteRegister LoRaSyncWordMsb [14 24]",
+            "SetPacketType GFSK",
+            "SetModulationParams [07 04 01 00]",
+            "SetModulationParams GfskModParams { bitrate: 50000, pulse_shape: BT0_5, \
+             bandwidth: BW117300, fdev: 25000 }",
+            "SetPacketParams GfskPacketParams { preamble_len: 32, preamble_detector_len: Bits16, \
+             sync_word_len: 16, addr_comp: Off, header_type: VarLen, payload_len: 255, \
+             crc_type: Crc2ByteInv, whitening: false }",
+        ];
+        assert_eq!(decode_trace(trace), expected);
+    }
+
+    #[test]
+    fn decode_cad_params_and_sleep() {
+        let trace: &[(&[u8], &[u8])] = &[
+            (&[0x88, 0x01, 0x16, 0x0A, 0x00, 0x00, 0x00, 0x00], &[]),
+            (&[0x88, 0x07, 0x16, 0x0A, 0x00, 0x00, 0x00, 0x00], &[]),
+            (&[0x84, 0x04], &[]),
+            (&[0x84, 0x02], &[]),
+        ];
+        let expected = [
+            "SetCadParams CadParams { symbol_num: Symbols2, det_peak: 22, det_min: 10, \
+             exit_mode: CadOnly, timeout: RxTxTimeout { inner: [0, 0, 0] } }",
+            "SetCadParams [07 16 0A 00 00 00 00]",
+            "SetSleep SleepConfig { warm_start: true, rtc_wakeup: false }",
+            "SetSleep 0x02",
+        ];
+        assert_eq!(decode_trace(trace), expected);
+    }
+
+    #[test]
+    fn decode_without_packet_type() {
+        let mosi = [0x8C, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00];
+        let transaction = decode(&mosi, &[]).unwrap();
+        assert_eq!(transaction.packet_type, None);
+        assert_eq!(
+            transaction.to_string(),
+            "SetPacketParams [00 08 00 00 00 00]"
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Recording and decoding of the SPI traffic between the driver and the modem.
+//!
+//! Wrap the SPI device in a [`Recorder`] to pass every nss-framed transaction to a [`Sink`].
+//! With the `std` feature enabled, [`Capture`] stores the transactions in memory.
+//! Captured transactions, or bytes taken from a logic analyzer dump, can be turned
+//! back into named commands using a [`Decoder`]:
+//!
+//! ```ignore
+//! let mut spi = trace::Recorder::new(spi, trace::Capture::default());
+//! sx.init(&mut spi, &mut delay, conf)?;
+//! let mut decoder = trace::Decoder::new();
+//! for frame in spi.sink().frames() {
+//!     println!("{}", decoder.decode(&frame.mosi, &frame.miso)?);
+//! }
+//! ```
+mod decode;
+
+pub use decode::*;
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+use embedded_hal::spi::{ErrorType, Operation, SpiDevice};
+
+const NOP: u8 = 0x00;
+
+/// Receives the bytes of recorded transactions
+pub trait Sink {
+    /// Called when a transaction starts, before any bytes are passed
+    fn start(&mut self) {}
+
+    /// Called with the bytes sent on mosi, in order
+    fn mosi(&mut self, data: &[u8]);
+
+    /// Called with the bytes received on miso, in order, after all bytes sent on mosi
+    /// have been passed. As the bytes received during write operations are discarded
+    /// by the SPI device, these are passed as NOP.
+    fn miso(&mut self, data: &[u8]);
+
+    /// Called when a transaction ends
+    fn end(&mut self) {}
+}
+
+/// SPI device that records every transaction passed to the wrapped SPI device
+pub struct Recorder<TSPI, TSINK> {
+    spi: TSPI,
+    sink: TSINK,
+}
+
+impl<TSPI, TSINK> Recorder<TSPI, TSINK> {
+    pub fn new(spi: TSPI, sink: TSINK) -> Self {
+        Self { spi, sink }
+    }
+
+    pub fn sink(&self) -> &TSINK {
+        &self.sink
+    }
+
+    pub fn sink_mut(&mut self) -> &mut TSINK {
+        &mut self.sink
+    }
+
+    /// Release the SPI device and sink
+    pub fn free(self) -> (TSPI, TSINK) {
+        (self.spi, self.sink)
+    }
+}
+
+impl<TSPI: ErrorType, TSINK> ErrorType for Recorder<TSPI, TSINK> {
+    type Error = TSPI::Error;
+}
+
+impl<TSPI: SpiDevice, TSINK: Sink> SpiDevice for Recorder<TSPI, TSINK> {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        self.sink.start();
+        // Bytes written in place are overwritten by the transaction, so record mosi first
+        for operation in operations.iter() {
+            match operation {
+                Operation::Read(words) => words.iter().for_each(|_| self.sink.mosi(&[NOP])),
+                Operation::Write(words) => self.sink.mosi(words),
+                Operation::TransferInPlace(words) => self.sink.mosi(words),
+                Operation::Transfer(read, write) => {
+                    self.sink.mosi(write);
+                    (write.len()..read.len()).for_each(|_| self.sink.mosi(&[NOP]));
+                }
+                Operation::DelayNs(_) => {}
+            }
+        }
+        let result = self.spi.transaction(operations);
+        for operation in operations.iter() {
+            match operation {
+                Operation::Read(words) => self.sink.miso(words),
+                Operation::TransferInPlace(words) => self.sink.miso(words),
+                Operation::Write(words) => words.iter().for_each(|_| self.sink.miso(&[NOP])),
+                Operation::Transfer(read, write) => {
+                    self.sink.miso(read);
+                    (read.len()..write.len()).for_each(|_| self.sink.miso(&[NOP]));
+                }
+                Operation::DelayNs(_) => {}
+            }
+        }
+        self.sink.end();
+        result
+    }
+}
+
+/// Bytes sent and received within a single transaction
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Frame {
+    pub mosi: Vec<u8>,
+    pub miso: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl Frame {
+    /// Decode the command in this frame
+    pub fn decode(&self) -> Result<Transaction<'_>, DecodeError> {
+        decode(&self.mosi, &self.miso)
+    }
+}
+
+/// Sink that stores the recorded transactions in memory
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default)]
+pub struct Capture {
+    frames: Vec<Frame>,
+}
+
+#[cfg(feature = "std")]
+impl Capture {
+    /// Transactions recorded so far
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Take the transactions recorded so far
+    pub fn take(&mut self) -> Vec<Frame> {
+        core::mem::take(&mut self.frames)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Sink for Capture {
+    fn start(&mut self) {
+        self.frames.push(Frame::default());
+    }
+
+    fn mosi(&mut self, data: &[u8]) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.mosi.extend_from_slice(data);
+        }
+    }
+
+    fn miso(&mut self, data: &[u8]) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.miso.extend_from_slice(data);
+        }
+    }
+}
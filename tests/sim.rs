@@ -0,0 +1,231 @@
+//! Drive the blocking driver against the simulated SX126x
+#![cfg(feature = "sim")]
+
+use core::convert::Infallible;
+use embedded_hal::digital::{ErrorType, InputPin};
+use sx126x::conf::Config;
+use sx126x::err::{RxError, SxError};
+use sx126x::op::irq::IrqMaskBit::{RxDone, Timeout, TxDone};
+use sx126x::op::modulation::lora::LoraModParams;
+use sx126x::op::packet::lora::LoRaCrcType;
+use sx126x::op::*;
+use sx126x::reg::OcpConfiguration;
+use sx126x::sim::{Device, Dio1, Mode};
+use sx126x::SX126x;
+
+const RF_FREQUENCY: Frequency = Frequency::from_hz(868_000_000);
+
+fn build_config() -> Config {
+    let pa_settings = OutputPower::dbm(14).pa_settings(Variant::SX1262).unwrap();
+    Config {
+        variant: Variant::SX1262,
+        regulator_mode: RegulatorMode::DcDc,
+        tcxo: None,
+        packet_type: PacketType::LoRa,
+        sync_word: 0x1424,
+        calib_param: CalibParam::all(),
+        mod_params: LoraModParams::default().into(),
+        tx_params: pa_settings.tx_params,
+        pa_config: pa_settings.pa_config,
+        packet_params: None,
+        dio1_irq_mask: IrqMask::none()
+            .combine(TxDone)
+            .combine(Timeout)
+            .combine(RxDone),
+        dio2_irq_mask: IrqMask::none(),
+        dio3_irq_mask: IrqMask::none(),
+        rf_frequency: RF_FREQUENCY,
+    }
+}
+
+/// Dio1 pin which makes the device receive `payload` the first time it is polled,
+/// that is once the driver has put the device in RX mode
+struct Incoming<'a> {
+    device: Device,
+    dio1: Dio1,
+    payload: Option<&'a [u8]>,
+}
+
+impl ErrorType for Incoming<'_> {
+    type Error = Infallible;
+}
+
+impl InputPin for Incoming<'_> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        if let Some(payload) = self.payload.take() {
+            assert!(self.device.receive(payload, -80, 10));
+        }
+        self.dio1.is_high()
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_high().map(|high| !high)
+    }
+}
+
+#[test]
+fn init() {
+    let device = Device::new();
+    let mut sx = SX126x::new((device.nrst(), device.busy(), device.ant()));
+    sx.init(&mut device.spi(), &mut device.delay(), build_config())
+        .unwrap();
+
+    assert_eq!(device.mode(), Mode::StbyRc);
+    assert_eq!(device.packet_type(), PacketType::LoRa as u8);
+    assert_eq!(device.rf_freq(), RF_FREQUENCY.rf_freq());
+    assert_eq!(
+        device.mod_params(),
+        <[u8; 8]>::from(ModParams::from(LoraModParams::default()))
+    );
+    // LoRa sync word registers
+    assert_eq!(
+        [device.register(0x740), device.register(0x741)],
+        [0x14, 0x24]
+    );
+}
+
+#[test]
+fn write_bytes() {
+    let device = Device::new();
+    let (mut spi, mut delay, mut dio1) = (device.spi(), device.delay(), device.dio1());
+    let mut sx = SX126x::new((device.nrst(), device.busy(), device.ant()));
+    sx.init(&mut spi, &mut delay, build_config()).unwrap();
+
+    let payload = b"Hello from sx126x-rs!";
+    let timeout = RxTxTimeout::from_ms(1000);
+    sx.write_bytes(
+        &mut spi,
+        &mut delay,
+        payload,
+        timeout,
+        8,
+        LoRaCrcType::CrcOn,
+        &mut dio1,
+    )
+    .unwrap();
+
+    assert_eq!(device.take_transmitted(), vec![payload.to_vec()]);
+    // Back in standby after TxDone, with the IRQ cleared
+    assert_eq!(device.mode(), Mode::StbyRc);
+    assert_eq!(device.irq_status(), 0);
+    assert!(device.take_transmitted().is_empty());
+}
+
+#[test]
+fn read_bytes() {
+    let device = Device::new();
+    let (mut spi, mut delay) = (device.spi(), device.delay());
+    let mut sx = SX126x::new((device.nrst(), device.busy(), device.ant()));
+    sx.init(&mut spi, &mut delay, build_config()).unwrap();
+
+    let payload = b"Hello from the other side";
+    let mut dio1 = Incoming {
+        device: device.clone(),
+        dio1: device.dio1(),
+        payload: Some(payload),
+    };
+    let mut buf = [0u8; 255];
+    let packet = sx
+        .read_bytes(
+            &mut spi,
+            &mut delay,
+            &mut buf,
+            RxTxTimeout::from_ms(1000),
+            &mut dio1,
+        )
+        .unwrap();
+
+    assert_eq!(&buf[..packet.len() as usize], &payload[..]);
+    let status = packet.packet_status().lora().unwrap();
+    assert_eq!(status.rssi_pkt(), -80.);
+    assert_eq!(status.snr_pkt(), 10.);
+    assert_eq!(device.irq_status(), 0);
+
+    // A buffer shorter than the payload gets its start
+    let mut dio1 = Incoming {
+        payload: Some(payload),
+        ..dio1
+    };
+    let mut buf = [0u8; 5];
+    let packet = sx
+        .read_bytes(
+            &mut spi,
+            &mut delay,
+            &mut buf,
+            RxTxTimeout::from_ms(1000),
+            &mut dio1,
+        )
+        .unwrap();
+    assert_eq!(packet.len(), 5);
+    assert_eq!(&buf, b"Hello");
+}
+
+#[test]
+fn read_bytes_timeout() {
+    let device = Device::new();
+    let (mut spi, mut delay, mut dio1) = (device.spi(), device.delay(), device.dio1());
+    let mut sx = SX126x::new((device.nrst(), device.busy(), device.ant()));
+    sx.init(&mut spi, &mut delay, build_config()).unwrap();
+
+    let start = device.clock().now();
+    let mut buf = [0u8; 255];
+    let result = sx.read_bytes(
+        &mut spi,
+        &mut delay,
+        &mut buf,
+        RxTxTimeout::from_ms(100),
+        &mut dio1,
+    );
+
+    assert!(matches!(result, Err(SxError::Rx(RxError::Timeout))));
+    assert_eq!(device.mode(), Mode::StbyRc);
+    // The modem timed out, not the wait on dio1
+    let elapsed_ms = (device.clock().now() - start) / 1_000_000;
+    assert!((100..110).contains(&elapsed_ms), "{} ms", elapsed_ms);
+}
+
+#[test]
+fn output_power_after_cold_start() {
+    let device = Device::new();
+    let (mut spi, mut delay) = (device.spi(), device.delay());
+    let mut sx = SX126x::new((device.nrst(), device.busy(), device.ant()));
+    sx.init(&mut spi, &mut delay, build_config()).unwrap();
+
+    let settings = OutputPower::dbm(14).pa_settings(Variant::SX1262).unwrap();
+    let settings = PaSettings {
+        ocp: OcpConfiguration::from_ma(100),
+        ..settings
+    };
+    sx.set_output_power(&mut spi, &mut delay, settings).unwrap();
+    assert_eq!(device.register(0x8E7), 0x28);
+
+    // The register is reset by the cold start, and restored on wake-up
+    let sleep_config = SleepConfig {
+        warm_start: false,
+        rtc_wakeup: false,
+    };
+    sx.set_sleep(&mut spi, &mut delay, sleep_config).unwrap();
+    sx.get_status(&mut spi, &mut delay).unwrap();
+    assert_eq!(device.mode(), Mode::StbyRc);
+    assert_eq!(device.register(0x8E7), 0x28);
+
+    // Setting the PA config on its own resets it
+    sx.set_pa_config(&mut spi, &mut delay, settings.pa_config)
+        .unwrap();
+    assert_eq!(device.register(0x8E7), 0x38);
+}
+
+#[test]
+fn rssi_out_of_range() {
+    let device = Device::new();
+    let (mut spi, mut delay) = (device.spi(), device.delay());
+    let mut sx = SX126x::new((device.nrst(), device.busy(), device.ant()));
+    sx.init(&mut spi, &mut delay, build_config()).unwrap();
+
+    // The packet status saturates at -127.5 dBm
+    sx.set_rx(&mut spi, &mut delay, RxTxTimeout::from_ms(1000))
+        .unwrap();
+    assert!(device.receive(b"faint", i16::MIN, -20));
+    let status = sx.get_packet_status(&mut spi, &mut delay).unwrap();
+    assert_eq!(status.lora().unwrap().rssi_pkt(), -127.5);
+}